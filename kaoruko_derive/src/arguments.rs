@@ -0,0 +1,197 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{Fields, GenericArgument, PathArguments, Token, Type, Variant};
+
+use crate::new_error;
+use crate::types::{Config, StringOption, SupportedStringArguments};
+
+/// A named field of a variant, filled from the words following the command name.
+pub(crate) struct Argument<'a> {
+    pub(crate) ident: &'a syn::Ident,
+    // the type inside the `Option`, for optional arguments
+    pub(crate) ty: &'a Type,
+    pub(crate) options: StringOption,
+}
+
+pub(crate) fn parse_arguments<'a>(
+    variant: &'a Variant,
+    config: &Config,
+) -> syn::Result<Vec<Argument<'a>>> {
+    let fields = match &variant.fields {
+        Fields::Unit => return Ok(Vec::new()),
+        Fields::Unnamed(fields) => {
+            return Err(new_error(
+                fields,
+                "command arguments must be named fields, e.g. `Search { query: String }`",
+            ));
+        }
+        Fields::Named(fields) => &fields.named,
+    };
+
+    let options = match &config.string_options {
+        Some((key, options)) => {
+            if options.len() != fields.len() {
+                return Err(new_error(
+                    key,
+                    &format!(
+                        "`string_options` has {} entries but `{}` has {} fields",
+                        options.len(),
+                        variant.ident,
+                        fields.len()
+                    ),
+                ));
+            }
+            options.clone()
+        }
+        None => fields.iter().map(|_| StringOption::default()).collect(),
+    };
+
+    let mut arguments = Vec::new();
+    for (index, (field, options)) in fields.iter().zip(options).enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let inner = option_inner(&field.ty);
+        if options.required && inner.is_some() {
+            return Err(new_error(
+                &field.ty,
+                &format!("`required` argument `{ident}` cannot be an `Option`"),
+            ));
+        }
+        if !options.required && inner.is_none() {
+            return Err(new_error(
+                &field.ty,
+                &format!("argument `{ident}` must be an `Option` unless it is `required`"),
+            ));
+        }
+        if options.allow_whitespaces && index + 1 != fields.len() {
+            return Err(new_error(
+                ident,
+                &format!("only the last argument can allow whitespaces, `{ident}` is not"),
+            ));
+        }
+        arguments.push(Argument {
+            ident,
+            ty: inner.unwrap_or(&field.ty),
+            options,
+        });
+    }
+    Ok(arguments)
+}
+
+/// `!name <required> [optional] [rest...]`
+pub(crate) fn usage(name: &str, arguments: &[Argument]) -> String {
+    let mut usage = format!("!{name}");
+    for argument in arguments {
+        let dots = if argument.options.allow_whitespaces {
+            "..."
+        } else {
+            ""
+        };
+        if argument.options.required {
+            usage.push_str(&format!(" <{}{dots}>", argument.ident));
+        } else {
+            usage.push_str(&format!(" [{}{dots}]", argument.ident));
+        }
+    }
+    usage
+}
+
+/// Code reading every argument out of `__rest`, binding each to its field name.
+pub(crate) fn parse_code(arguments: &[Argument], usage: &str) -> TokenStream {
+    let usage = Literal::string(usage);
+    let arguments = arguments.iter().map(|argument| {
+        let Argument { ident, ty, options } = argument;
+        let split = if options.allow_whitespaces {
+            quote! { let (__arg, __rest) = (__rest.trim(), ""); }
+        } else {
+            quote! {
+                let (__arg, __rest) = {
+                    let __rest = __rest.trim_start();
+                    match __rest.find(char::is_whitespace) {
+                        Some(index) => __rest.split_at(index),
+                        None => (__rest, ""),
+                    }
+                };
+            }
+        };
+        let name = Literal::string(&ident.to_string());
+        let value = quote! {
+            __arg.parse::<#ty>().map_err(|err| CommandError::InvalidArgument {
+                argument: #name,
+                error: err.to_string(),
+                usage: #usage,
+            })?
+        };
+        let value = if options.required {
+            quote! {
+                if __arg.is_empty() {
+                    return Err(CommandError::MissingArgument {
+                        argument: #name,
+                        usage: #usage,
+                    }
+                    .into());
+                }
+                let #ident = #value;
+            }
+        } else {
+            quote! {
+                let #ident = if __arg.is_empty() { None } else { Some(#value) };
+            }
+        };
+        quote! { #split #value }
+    });
+    quote! {
+        #(#arguments)*
+        if !__rest.trim().is_empty() {
+            return Err(CommandError::TooManyArguments { usage: #usage }.into());
+        }
+    }
+}
+
+// `(required, allow_whitespaces), (required)`: one group per field
+pub(crate) fn parse_string_options(input: ParseStream) -> syn::Result<Vec<StringOption>> {
+    let mut options = Vec::new();
+    while !input.is_empty() {
+        let content;
+        syn::parenthesized!(content in input);
+        let mut option = StringOption::default();
+        for ident in Punctuated::<syn::Ident, Token![,]>::parse_terminated(&content)? {
+            match ident.to_string().parse::<SupportedStringArguments>() {
+                Ok(SupportedStringArguments::Required) => option.required = true,
+                Ok(SupportedStringArguments::AllowWhitespaces) => option.allow_whitespaces = true,
+                Err(_) => {
+                    return Err(new_error(
+                        &ident,
+                        &format!(
+                            "unknown string option `{ident}`, expected `required` or `allow_whitespaces`"
+                        ),
+                    ));
+                }
+            }
+        }
+        options.push(option);
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    Ok(options)
+}
+
+// `T` for `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(ref arguments) = segment.arguments else {
+        return None;
+    };
+    match arguments.args.first() {
+        Some(GenericArgument::Type(inner)) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
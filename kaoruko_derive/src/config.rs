@@ -0,0 +1,294 @@
+use proc_macro2::TokenTree;
+use quote::ToTokens;
+use syn::parse::ParseStream;
+use syn::{Attribute, Ident, LitStr, Path, Token, Variant, bracketed, token};
+
+use crate::arguments::parse_string_options;
+use crate::new_error;
+use crate::types::{Config, Cooldown, Roles, SupportedArguments};
+
+/// Whether `#[config(reference_json)]` is set on the enum, the only option it takes.
+pub(crate) fn parse_enum_config(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut reference_json = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("config")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("reference_json") {
+                reference_json = true;
+                return Ok(());
+            }
+            let key = meta.path.to_token_stream();
+            Err(meta.error(format!(
+                "unknown enum config key `{key}`, expected: reference_json"
+            )))
+        })?;
+    }
+    Ok(reference_json)
+}
+
+/// Parses the `#[config(...)]` attribute of `variant`.
+pub(crate) fn parse_config(variant: &Variant) -> syn::Result<Config> {
+    let name = &variant.ident;
+    let mut attrs = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("config"));
+    let Some(attr) = attrs.next() else {
+        return Err(new_error(
+            name,
+            &format!("variant `{name}` must define `config` attribute"),
+        ));
+    };
+    if let Some(attr) = attrs.next() {
+        return Err(new_error(
+            attr,
+            &format!("variant `{name}` must define a single `config` attribute"),
+        ));
+    }
+
+    let mut alias = None;
+    let mut description = None;
+    let mut roles = None;
+    let mut string_options = None;
+    let mut cooldown = Cooldown::default();
+    let mut handler = None;
+    attr.parse_args_with(|input: ParseStream| {
+        let mut seen = Vec::new();
+        while !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+            let argument = key.to_string().parse::<SupportedArguments>().map_err(|_| {
+                new_error(
+                    &key,
+                    &format!(
+                        "unknown config key `{key}`, expected one of: {}",
+                        SupportedArguments::NAMES.join(", ")
+                    ),
+                )
+            })?;
+            if seen.contains(&argument) {
+                return Err(new_error(
+                    &key,
+                    &format!("duplicate attribute found: {key}"),
+                ));
+            }
+            seen.push(argument);
+
+            // `string_options(...)` and `description(en = "...")` take no `=`
+            let listed = argument == SupportedArguments::StringOptions
+                || (argument == SupportedArguments::Description && input.peek(token::Paren));
+            if !listed {
+                if !input.peek(Token![=]) {
+                    return Err(new_error(
+                        &key,
+                        &format!("expected punct `=` after `{key}`"),
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+            }
+            match argument {
+                SupportedArguments::Alias => {
+                    let literal = string(input, &key)?;
+                    if literal.value().is_empty() {
+                        return Err(new_error(&literal, "expected non empty literal"));
+                    }
+                    alias = Some(literal);
+                }
+                SupportedArguments::Description => {
+                    description = Some(if input.peek(token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in input);
+                        localized(&content, &key)?
+                    } else {
+                        vec![("en".to_string(), string(input, &key)?)]
+                    });
+                }
+                SupportedArguments::Roles => roles = Some(role_list(input, &key)?),
+                SupportedArguments::StringOptions => {
+                    if !input.peek(token::Paren) {
+                        return Err(input.error("expected `(...)` after `string_options`"));
+                    }
+                    let content;
+                    syn::parenthesized!(content in input);
+                    string_options = Some((key, parse_string_options(&content)?));
+                }
+                SupportedArguments::Cooldown | SupportedArguments::GlobalCooldown => {
+                    let literal = string(input, &key)?;
+                    let millis = parse_duration(&literal.value()).ok_or_else(|| {
+                        new_error(
+                            &literal,
+                            "expected a duration such as \"500ms\", \"5s\" or \"1m\"",
+                        )
+                    })?;
+                    if argument == SupportedArguments::Cooldown {
+                        cooldown.per_user = Some(millis);
+                    } else {
+                        cooldown.global = Some(millis);
+                    }
+                }
+                SupportedArguments::CooldownExempt => {
+                    let exempt = role_list(input, &key)?;
+                    if exempt.contains(&Roles::Anyone) {
+                        return Err(new_error(&key, "`anyone` cannot be exempt from a cooldown"));
+                    }
+                    cooldown.exempt = exempt;
+                }
+                SupportedArguments::Handler => {
+                    if !(input.peek(Ident)
+                        || input.peek(Token![crate])
+                        || input.peek(Token![self])
+                        || input.peek(Token![super])
+                        || input.peek(Token![::]))
+                    {
+                        let token = input.parse::<TokenTree>()?;
+                        return Err(new_error(
+                            &token,
+                            "expected a path to an async fn for `handler`",
+                        ));
+                    }
+                    handler = Some(input.parse::<Path>()?);
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(())
+    })?;
+
+    let Some(descriptions) = description else {
+        return Err(new_error(
+            name,
+            &format!("variant `{name}` must define `description` attribute"),
+        ));
+    };
+    let Some(roles) = roles else {
+        return Err(new_error(
+            name,
+            &format!("variant `{name}` must define `roles` attribute"),
+        ));
+    };
+    Ok(Config {
+        alias,
+        descriptions,
+        roles,
+        string_options,
+        cooldown,
+        handler,
+    })
+}
+
+// a string literal, or an error naming `key`
+fn string(input: ParseStream, key: &Ident) -> syn::Result<LitStr> {
+    if input.peek(LitStr) {
+        return input.parse();
+    }
+    let token = input.parse::<TokenTree>()?;
+    Err(new_error(
+        &token,
+        &format!("expected a string literal for `{key}`"),
+    ))
+}
+
+// `en = "...", fr = "..."`, `en` being required as the fallback
+fn localized(input: ParseStream, key: &Ident) -> syn::Result<Vec<(String, LitStr)>> {
+    let mut entries = Vec::<(String, LitStr)>::new();
+    let mut fallback = None;
+    while !input.is_empty() {
+        let locale = input.parse::<Ident>()?;
+        let code = locale.to_string();
+        if code.len() != 2 || !code.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(new_error(
+                &locale,
+                &format!("expected a two letter language code, e.g. `en`, found `{code}`"),
+            ));
+        }
+        if entries.iter().any(|(seen, _)| *seen == code) || fallback.is_some() && code == "en" {
+            return Err(new_error(&locale, &format!("duplicate locale `{code}`")));
+        }
+        input.parse::<Token![=]>()?;
+        let text = string(input, &locale)?;
+        if code == "en" {
+            fallback = Some(text);
+        } else {
+            entries.push((code, text));
+        }
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    let Some(fallback) = fallback else {
+        return Err(new_error(
+            key,
+            &format!("`{key}` must have an `en` entry, used when a language has no translation"),
+        ));
+    };
+    entries.insert(0, ("en".to_string(), fallback));
+    Ok(entries)
+}
+
+// `["developer", "creator"]`
+fn role_list(input: ParseStream, key: &Ident) -> syn::Result<Vec<Roles>> {
+    if !input.peek(token::Bracket) {
+        let token = input.parse::<TokenTree>()?;
+        return Err(new_error(
+            &token,
+            &format!("expected a list of roles for `{key}`, e.g. `[\"anyone\"]`"),
+        ));
+    }
+    let content;
+    bracketed!(content in input);
+    let mut roles = Vec::new();
+    while !content.is_empty() {
+        let literal = string(&content, key)?;
+        let role = literal.value().parse::<Roles>().map_err(|_| {
+            new_error(
+                &literal,
+                &format!(
+                    "unknown role `{}`, expected one of: {}",
+                    literal.value(),
+                    Roles::NAMES.join(", ")
+                ),
+            )
+        })?;
+        if roles.contains(&role) {
+            return Err(new_error(
+                &literal,
+                &format!("duplicate role `{}`", literal.value()),
+            ));
+        }
+        roles.push(role);
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+    if roles.is_empty() {
+        return Err(new_error(
+            key,
+            &format!("`{key}` expects at least one role"),
+        ));
+    }
+    if roles.len() > 1 && roles.contains(&Roles::Anyone) {
+        return Err(new_error(
+            key,
+            "`anyone` cannot be combined with other roles",
+        ));
+    }
+    Ok(roles)
+}
+
+// `"500ms"`, `"5s"` or `"1m"`, in milliseconds
+fn parse_duration(duration: &str) -> Option<u64> {
+    let unit_at = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = duration.split_at(unit_at);
+    let amount = amount.parse::<u64>().ok()?;
+    let unit = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
@@ -0,0 +1,65 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::role_paths;
+use crate::types::Cooldown;
+
+/// Cooldown fields of the generated `CommandInfo`.
+pub(crate) fn info_fields(cooldown: &Cooldown) -> TokenStream {
+    let duration = |millis: Option<u64>| match millis {
+        Some(millis) => quote! { Some(std::time::Duration::from_millis(#millis)) },
+        None => quote! { None },
+    };
+    let per_user = duration(cooldown.per_user);
+    let global = duration(cooldown.global);
+    let exempt = role_paths(&cooldown.exempt);
+    quote! {
+        cooldown: #per_user,
+        global_cooldown: #global,
+        cooldown_exempt: &[#(#exempt),*],
+    }
+}
+
+/// Code refusing the command while `info` is cooling down for `user`, who is holding `roles`.
+///
+/// `user` should outlive a rejoin, such as an account id, or their cooldown starts over.
+pub(crate) fn check_code(name: &syn::Ident) -> TokenStream {
+    quote! {
+        // when every command can be used again, globally and by user
+        static READY_AT: std::sync::LazyLock<
+            std::sync::Mutex<
+                std::collections::HashMap<(&'static str, Option<String>), std::time::Instant>,
+            >,
+        > = std::sync::LazyLock::new(Default::default);
+
+        let exempt = info.cooldown_exempt.iter().any(|role| roles.contains(role));
+        let now = std::time::Instant::now();
+        let mut ready_at = READY_AT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // cooldowns that are over would only grow the map
+        ready_at.retain(|_, ready_at| *ready_at > now);
+        let keys = [
+            (info.global_cooldown, (info.name, None)),
+            (info.cooldown, (info.name, Some(user.to_string()))),
+        ];
+        if let Some(ready_at) = keys.iter().find_map(|(_, key)| ready_at.get(key)) {
+            if !exempt {
+                return Err(CommandError::CoolingDown {
+                    command: info.name,
+                    seconds: ready_at.saturating_duration_since(now).as_secs_f64().ceil() as u64,
+                }
+                .into());
+            }
+        }
+        let command = self.parse::<#name>()?;
+        if !exempt {
+            for (cooldown, key) in keys {
+                if let Some(cooldown) = cooldown {
+                    ready_at.insert(key, now + cooldown);
+                }
+            }
+        }
+        Ok(command)
+    }
+}
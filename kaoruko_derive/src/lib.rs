@@ -1,13 +1,23 @@
 use proc_macro::TokenStream;
-use proc_macro2::{Literal, TokenTree};
+use proc_macro2::Literal;
 use quote::{ToTokens, quote};
-use std::collections::HashMap;
-use syn::{Data, DataEnum, DeriveInput, Meta};
+use syn::{Data, DeriveInput};
+mod arguments;
+mod config;
+mod cooldown;
+mod reference;
 mod types;
 
+use arguments::{parse_arguments, parse_code, usage};
+use config::{parse_config, parse_enum_config};
+use cooldown::{check_code, info_fields};
+use reference::reference_code;
 use types::*;
 
-// NEXT: parse the queries.
+/// Parses chat commands into the variants of an enum, each declaring its `#[config(...)]`.
+///
+/// `#[config(reference_json)]` on the enum also generates `reference_json`, which needs `serde`,
+/// with its `derive` feature, and `serde_json` as dependencies of the crate.
 #[proc_macro_derive(CommandParser, attributes(config))]
 pub fn derive(input: TokenStream) -> TokenStream {
     match derive_internal(input) {
@@ -24,121 +34,358 @@ fn derive_internal(input: TokenStream) -> syn::Result<proc_macro2::TokenStream>
         panic!("macro can only be applied on enums");
     };
 
-    // we don't care if enum has no variants
     if enumeration.variants.is_empty() {
-        return Ok(quote! {});
+        return Err(new_error(
+            name,
+            &format!("`{name}` must have at least one command variant"),
+        ));
     }
 
-    for variant in &enumeration.variants {
-        for attr in &variant.attrs {
-            let Meta::List(list) = &attr.meta else {
-                continue;
-            };
-            // for token in list.tokens.clone().into_iter() {
-            //     token
-            //         .to_string()
-            //         .parse::<SupportedArguments>()
-            //         .map_err(|_| new_error(&token, "unexpected tken"))?;
-            // }
-            check_duplicates(list.tokens.clone())?;
-            check_required(
-                list.tokens.clone(),
-                &["description", "roles"],
-                &variant.ident,
-            )?;
+    let reference_json = parse_enum_config(&input.attrs)?;
+    let configs = enumeration
+        .variants
+        .iter()
+        .map(parse_config)
+        .collect::<syn::Result<Vec<_>>>()?;
+    check_collisions(enumeration.variants.iter().zip(&configs))?;
+    check_handlers(enumeration.variants.iter().zip(&configs))?;
+
+    let mut infos = Vec::new();
+    let mut info_arms = Vec::new();
+    let mut from_str_arms = Vec::new();
+    let mut dispatch_arms = Vec::new();
+    for (index, (variant, config)) in enumeration.variants.iter().zip(&configs).enumerate() {
+        let arguments = parse_arguments(variant, config)?;
+        let cooldown = info_fields(&config.cooldown);
+        let variant = &variant.ident;
+        let command = Literal::string(&variant.to_string().to_lowercase());
+        let aliases = config.alias.iter().collect::<Vec<_>>();
+        let description = &config.descriptions[0].1;
+        let descriptions = config.descriptions.iter().map(|(locale, text)| {
+            let locale = Literal::string(locale);
+            quote! { (#locale, #text) }
+        });
+        let roles = role_paths(&config.roles);
+        let usage = usage(&variant.to_string().to_lowercase(), &arguments);
+        let parse = parse_code(&arguments, &usage);
+        let fields = arguments.iter().map(|argument| argument.ident);
+        let argument_infos = arguments.iter().map(|argument| {
+            let name = Literal::string(&argument.ident.to_string());
+            let required = argument.options.required;
+            let allow_whitespaces = argument.options.allow_whitespaces;
+            quote! {
+                ArgumentInfo {
+                    name: #name,
+                    required: #required,
+                    allow_whitespaces: #allow_whitespaces,
+                }
+            }
+        });
+
+        let usage = Literal::string(&usage);
+        infos.push(quote! {
+            CommandInfo {
+                name: #command,
+                aliases: &[#(#aliases),*],
+                description: #description,
+                descriptions: &[#(#descriptions),*],
+                roles: &[#(#roles),*],
+                usage: #usage,
+                arguments: &[#(#argument_infos),*],
+                #cooldown
+            }
+        });
+        info_arms.push(quote! { Self::#variant { .. } => &Self::COMMANDS[#index] });
+        if let Some(handler) = &config.handler {
+            let fields = arguments.iter().map(|argument| argument.ident);
+            // renamed, a field could shadow the handler
+            let values = arguments
+                .iter()
+                .map(|argument| quote::format_ident!("__{}", argument.ident))
+                .collect::<Vec<_>>();
+            dispatch_arms.push(quote! {
+                Self::#variant { #(#fields: #values),* } => #handler(ctx, #(#values),*).await
+            });
         }
+        from_str_arms.push(quote! {
+            #command #(| #aliases)* => {
+                #parse
+                Ok(Self::#variant { #(#fields),* })
+            }
+        });
     }
 
-    let aliases = parse_aliases(enumeration)?;
-    let descriptions = parse_description(enumeration)?;
-    let roles = parse_roles(enumeration)?;
-
-    let condition_blocks = roles.iter().map(|(variant, roles)| {
-        let condition = roles.iter().map(|literal| {
-            // TODO: improve this
-            if &literal.to_string() == "\"developer\"" {
-                quote! {
-                    let mut only_developer = false;
-                    for role in &roles {
-                        if role == #literal {
-                            only_developer = true;
-                        }
+    // only when every command has a handler
+    let dispatch = (!dispatch_arms.is_empty()).then(|| {
+        quote! {
+            impl #name {
+                /// Runs the handler of the command, returning its reply if there is one.
+                pub async fn dispatch(self, ctx: &crate::Context) -> Option<String> {
+                    match self {
+                        #(#dispatch_arms,)*
                     }
-                    let auth_default = crate::Auth::default();
-                    let discord_id = &auth.as_ref().unwrap_or(&&auth_default).id;
-                    if !only_developer && discord_id != "988839581384323083" {
-                        return Err(anyhow::anyhow!("you are not eligible to use this command"));
+                }
+            }
+        }
+    });
+    let check = check_code(name);
+    let reference = reference_code(name, reference_json);
+    let stream = quote! {
+        /// Argument of a command, as declared in its `string_options`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ArgumentInfo {
+            pub name: &'static str,
+            pub required: bool,
+            pub allow_whitespaces: bool,
+        }
+
+        /// Everything declared about a command in its `config` attribute.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct CommandInfo {
+            pub name: &'static str,
+            pub aliases: &'static [&'static str],
+            // in english
+            pub description: &'static str,
+            // by language code, english included
+            pub descriptions: &'static [(&'static str, &'static str)],
+            pub roles: &'static [crate::Role],
+            pub usage: &'static str,
+            pub arguments: &'static [ArgumentInfo],
+            // per user
+            pub cooldown: Option<std::time::Duration>,
+            pub global_cooldown: Option<std::time::Duration>,
+            pub cooldown_exempt: &'static [crate::Role],
+        }
+
+        impl CommandInfo {
+            /// Whether `name` is the name or one of the aliases of the command.
+            pub fn matches(&self, name: &str) -> bool {
+                self.name == name || self.aliases.contains(&name)
+            }
+
+            /// Whether a chatter holding `roles` may use the command.
+            pub fn is_permitted(&self, roles: &[crate::Role]) -> bool {
+                // no roles means anyone, and developers may use every command
+                self.roles.is_empty()
+                    || roles.contains(&crate::Role::Developer)
+                    || self.roles.iter().any(|role| roles.contains(role))
+            }
+        }
+
+        impl CommandInfo {
+            /// The description for `locale`, such as `fr` or `fr-FR`, falling back to english.
+            pub fn description_in(&self, locale: &str) -> &'static str {
+                let language = locale.split(['-', '_']).next().unwrap_or_default();
+                self.descriptions
+                    .iter()
+                    .find(|(code, _)| code.eq_ignore_ascii_case(language))
+                    .map_or(self.description, |(_, description)| description)
+            }
+
+            /// `description — usage: !name <arg> — aliases: a — roles: developer`
+            pub fn help(&self, locale: &str) -> String {
+                let mut help = format!("{} — usage: {}", self.description_in(locale), self.usage);
+                if !self.aliases.is_empty() {
+                    help.push_str(&format!(" — aliases: {}", self.aliases.join(", ")));
+                }
+                if !self.roles.is_empty() {
+                    let roles = self.roles.iter().map(|role| role.to_string()).collect::<Vec<_>>();
+                    help.push_str(&format!(" — roles: {}", roles.join(", ")));
+                }
+                help
+            }
+        }
+
+        impl std::fmt::Display for CommandInfo {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.help("en"))
+            }
+        }
+
+        /// A name matching no command, along with the closest names and aliases.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct UnknownCommand {
+            pub name: String,
+            pub suggestions: Vec<&'static str>,
+        }
+
+        impl std::fmt::Display for UnknownCommand {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("unknown command")?;
+                match self.suggestions.as_slice() {
+                    [] => Ok(()),
+                    [only] => write!(f, " — did you mean !{only}?"),
+                    [rest @ .., last] => {
+                        let rest = rest.iter().map(|name| format!("!{name}")).collect::<Vec<_>>();
+                        write!(f, " — did you mean {} or !{last}?", rest.join(", "))
                     }
                 }
-            } else if &literal.to_string() == "\"creator\"" {
-                quote! {
-                    let mut only_creator = false;
-                    for role in &roles {
-                        if role == #literal {
-                            only_creator = true;
-                        }
+            }
+        }
+
+        impl std::error::Error for UnknownCommand {}
+
+        /// Why a known command was refused, worded in english unless the caller words it.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum CommandError {
+            NotEligible,
+            CoolingDown {
+                command: &'static str,
+                seconds: u64,
+            },
+            MissingArgument {
+                argument: &'static str,
+                usage: &'static str,
+            },
+            InvalidArgument {
+                argument: &'static str,
+                error: String,
+                usage: &'static str,
+            },
+            TooManyArguments {
+                usage: &'static str,
+            },
+        }
+
+        impl std::fmt::Display for CommandError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::NotEligible => f.write_str("you are not eligible to use this command"),
+                    Self::CoolingDown { command, seconds } => {
+                        write!(f, "wait {seconds}s before using !{command} again")
                     }
-                    let auth_default = crate::Auth::default();
-                    let discord_id = &auth.as_ref().unwrap_or(&&auth_default).id;
-                    if discord_id == "988839581384323083" { return Ok(#name::#variant); }
-                    if !only_creator && &room_creator != discord_id {
-                        return Err(anyhow::anyhow!("you are not eligible to use this command"));
+                    Self::MissingArgument { argument, usage } => {
+                        write!(f, "missing argument <{argument}> — usage: {usage}")
+                    }
+                    Self::InvalidArgument { argument, error, usage } => {
+                        write!(f, "invalid argument <{argument}>: {error} — usage: {usage}")
+                    }
+                    Self::TooManyArguments { usage } => {
+                        write!(f, "too many arguments — usage: {usage}")
                     }
                 }
-            } else {
-                // covers "\"anyone\""
-                quote! {}
-            }
-        });
-        quote! {
-            #name::#variant => {
-                #(#condition)*
-                return Ok(#name::#variant);
             }
         }
-    });
 
-    let stream = quote! {
+        impl std::error::Error for CommandError {}
+
         impl std::str::FromStr for #name {
             type Err = anyhow::Error;
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match s {
-                   #(#aliases,)*
-                    _ => Err(anyhow::anyhow!("unknown command")),
+                let s = s.trim_start();
+                let (__name, __rest) = match s.find(char::is_whitespace) {
+                    Some(index) => s.split_at(index),
+                    None => (s, ""),
+                };
+                match __name {
+                   #(#from_str_arms)*
+                    _ => Err(Self::unknown(__name)),
                 }
             }
         }
 
         impl #name {
-            pub fn help(&self) -> &'static str {
+            /// Every command, in declaration order.
+            pub const COMMANDS: &'static [CommandInfo] = &[#(#infos),*];
+
+            pub fn info(&self) -> &'static CommandInfo {
                 match self {
-                   #(#descriptions,)*
+                   #(#info_arms,)*
+                }
+            }
+
+            /// The command called `name`, which may be an alias.
+            pub fn find(name: &str) -> Option<&'static CommandInfo> {
+                Self::COMMANDS.iter().find(|info| info.matches(name))
+            }
+
+            pub fn help(&self) -> &'static str {
+                self.info().description
+            }
+
+            pub fn name(&self) -> &'static str {
+                self.info().name
+            }
+
+            pub fn usage(&self) -> &'static str {
+                self.info().usage
+            }
+
+            /// Names and aliases close to `name`, closest first, at most one per command.
+            pub fn suggest(name: &str) -> Vec<&'static str> {
+                let mut suggestions = Self::COMMANDS
+                    .iter()
+                    .filter_map(|info| {
+                        std::iter::once(info.name)
+                            .chain(info.aliases.iter().copied())
+                            .map(|candidate| (Self::edit_distance(name, candidate), candidate))
+                            // a single letter is one edit away from any other alias
+                            .filter(|(distance, candidate)| {
+                                let length = name.chars().count().max(candidate.chars().count());
+                                *distance <= 2 && *distance < length
+                            })
+                            .min()
+                    })
+                    .collect::<Vec<_>>();
+                suggestions.sort();
+                suggestions
+                    .into_iter()
+                    .take(3)
+                    .map(|(_, candidate)| candidate)
+                    .collect()
+            }
+
+            fn unknown(name: &str) -> anyhow::Error {
+                anyhow::Error::new(UnknownCommand {
+                    name: name.to_string(),
+                    suggestions: Self::suggest(name),
+                })
+            }
+
+            // levenshtein distance, over chars
+            fn edit_distance(a: &str, b: &str) -> usize {
+                let b = b.chars().collect::<Vec<_>>();
+                let mut previous = (0..=b.len()).collect::<Vec<_>>();
+                for (i, a) in a.chars().enumerate() {
+                    let mut current = vec![i + 1];
+                    for (j, b) in b.iter().enumerate() {
+                        let substitution = previous[j] + usize::from(a != *b);
+                        current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+                    }
+                    previous = current;
+                }
+                previous[b.len()]
+            }
+
+            /// Description in `locale`, usage, aliases and roles of the command called `name`.
+            pub fn help_for(name: &str, locale: &str) -> anyhow::Result<String> {
+                match Self::find(name) {
+                    Some(info) => Ok(info.help(locale)),
+                    None => Err(Self::unknown(name)),
                 }
             }
         }
 
+        #dispatch
+
+        #reference
+
         pub trait CommandParserTrait {
-            fn parse_command(
-                &self,
-                roles: Vec<String>,
-                auth: Option<&crate::Auth>,
-                room_creator: String
-            ) -> anyhow::Result<#name>;
+            fn parse_command(&self, roles: &[crate::Role], user: &str) -> anyhow::Result<#name>;
         }
 
         impl CommandParserTrait for &str {
-            fn parse_command(
-                &self,
-                roles: Vec<String>,
-                auth: Option<&crate::Auth>,
-                room_creator: String
-            ) -> anyhow::Result<#name> {
-                match self.parse::<#name>() {
-                    Ok(cmd) => match cmd {
-                        #(#condition_blocks)*
-                    },
-                    Err(err) => Err(err),
+            /// Parses a command and its arguments, once `user` holding `roles` is known to be
+            /// eligible and the command is not cooling down.
+            fn parse_command(&self, roles: &[crate::Role], user: &str) -> anyhow::Result<#name> {
+                let __name = self.split_whitespace().next().unwrap_or_default();
+                let Some(info) = #name::find(__name) else {
+                    return Err(#name::unknown(__name));
+                };
+                if !info.is_permitted(roles) {
+                    return Err(CommandError::NotEligible.into());
                 }
+                #check
             }
         }
     };
@@ -150,220 +397,70 @@ fn new_error<T: ToTokens>(token_stream: &T, error: &str) -> syn::Error {
     syn::Error::new_spanned(token_stream, error)
 }
 
-fn check_duplicates(stream: proc_macro2::TokenStream) -> Result<(), syn::Error> {
-    let mut idents = Vec::<syn::Ident>::new();
-    for token in stream {
-        if let TokenTree::Ident(ref ident) = token {
-            if idents.contains(ident) {
-                return Err(new_error(
-                    ident,
-                    &format!("duplicate attribute found: {ident}"),
-                ));
-            }
-            idents.push(ident.clone());
+// every name and alias must resolve to a single variant
+fn check_collisions<'a>(
+    variants: impl Iterator<Item = (&'a syn::Variant, &'a Config)>,
+) -> syn::Result<()> {
+    let mut seen = Vec::<(String, &'a syn::Ident, proc_macro2::Span)>::new();
+    for (variant, config) in variants {
+        let mut names = vec![(
+            variant.ident.to_string().to_lowercase(),
+            variant.ident.span(),
+        )];
+        if let Some(alias) = &config.alias {
+            names.push((alias.value(), alias.span()));
         }
-    }
-    Ok(())
-}
-
-fn check_required(
-    stream: proc_macro2::TokenStream,
-    attrs: &[&'static str],
-    variant: &syn::Ident,
-) -> Result<(), syn::Error> {
-    let mut contains = false;
-    for attr in attrs {
-        for token in stream.clone() {
-            if let TokenTree::Ident(ref ident) = token {
-                if attr == &ident.to_string() {
-                    contains = true;
-                }
+        for (name, span) in names {
+            if let Some((_, first, first_span)) = seen.iter().find(|(seen, ..)| *seen == name) {
+                let mut err = syn::Error::new(
+                    span,
+                    format!(
+                        "`{name}` of `{}` is already used by `{first}`",
+                        variant.ident
+                    ),
+                );
+                err.combine(syn::Error::new(
+                    *first_span,
+                    format!("`{name}` is first used by `{first}` here"),
+                ));
+                return Err(err);
             }
+            seen.push((name, &variant.ident, span));
         }
-        if !contains {
-            return Err(new_error(
-                &variant,
-                &format!("variant `{variant}` must define `{attr}` attribute"),
-            ));
-        }
-        contains = false;
     }
     Ok(())
 }
 
-// TODO: better error handling
-fn parse_description(enumeration: &DataEnum) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let mut descriptions = Vec::<proc_macro2::TokenStream>::new();
-    for variant in &enumeration.variants {
-        for attr in &variant.attrs {
-            let Meta::List(ref list) = attr.meta else {
-                continue;
-            };
-            let mut iter = list.tokens.clone().into_iter();
-            while let Some(token) = iter.next() {
-                if let TokenTree::Ident(ref ident) = token {
-                    let SupportedArguments::Alias = ident
-                        .to_string()
-                        .parse::<SupportedArguments>()
-                        .map_err(|_| {
-                            new_error(ident, &format!("unknown variant attribute `{ident}"))
-                        })?
-                    else {
-                        continue;
-                    };
-                    let Some(TokenTree::Punct(ref punct)) = iter.next() else {
-                        return Err(new_error(
-                            &ident,
-                            &format!("expected punct `=` after `{ident}`"),
-                        ));
-                    };
-                    if punct.to_string() != "=" {
-                        return Err(new_error(
-                            &punct,
-                            &format!("expected punct `=` after `{ident}`"),
-                        ));
-                    }
-                }
-                // move this inside the last curly with proper parsing
-                if let Some(TokenTree::Literal(ref literal)) = iter.next() {
-                    let variant = &variant.ident;
-                    descriptions.push(quote! { Self::#variant => #literal });
-                }
-            }
-        }
-    }
-    Ok(descriptions)
-}
-
-fn parse_aliases(enumeration: &DataEnum) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let mut aliases = Vec::<proc_macro2::TokenStream>::new();
-    for variant in &enumeration.variants {
-        let name = &variant.ident;
-        let literal = Literal::string(&name.to_string().to_lowercase());
-        let mut stream = quote! { #literal };
-        for attr in &variant.attrs {
-            let Meta::List(ref list) = attr.meta else {
-                continue;
-            };
-            let mut iter = list.tokens.clone().into_iter();
-            while let Some(TokenTree::Ident(ref ident)) = iter.next() {
-                let SupportedArguments::Alias = ident
-                    .to_string()
-                    .parse::<SupportedArguments>()
-                    .map_err(|_| {
-                        new_error(ident, &format!("unknown variant attribute `{ident}"))
-                    })?
-                else {
-                    continue;
-                };
-                if let Some(TokenTree::Punct(ref punct)) = iter.next() {
-                    if let Some(TokenTree::Literal(ref alias)) = iter.next() {
-                        if alias.to_string() == "\"\"" {
-                            return Err(new_error(&alias, "expected non empty literal"));
-                        }
-                        let Some(TokenTree::Punct(_)) = iter.next() else {
-                            return Err(new_error(
-                                &alias,
-                                &format!("expected punct `,` after `{alias}`"),
-                            ));
-                        };
-                        stream.extend(quote! { | #alias });
-                    } else if punct.to_string() != "=" {
-                        return Err(new_error(
-                            &punct,
-                            &format!("expected punct `=` after `{ident}`"),
-                        ));
-                    } else {
-                        return Err(new_error(
-                            &punct,
-                            &format!("expected literal after `{punct}`"),
-                        ));
-                    }
-                } else {
-                    return Err(new_error(
-                        &ident,
-                        &format!("expected punct `=` after `{ident}`"),
-                    ));
-                }
-            }
-        }
-        stream.extend(quote! { => Ok(Self::#name) });
-        aliases.push(stream);
+// handlers are either declared on every variant or on none
+fn check_handlers<'a>(
+    mut variants: impl Iterator<Item = (&'a syn::Variant, &'a Config)> + Clone,
+) -> syn::Result<()> {
+    let Some((with, _)) = variants
+        .clone()
+        .find(|(_, config)| config.handler.is_some())
+    else {
+        return Ok(());
+    };
+    match variants.find(|(_, config)| config.handler.is_none()) {
+        Some((without, _)) => Err(new_error(
+            &without.ident,
+            &format!(
+                "variant `{}` must define `handler` attribute, as `{}` does",
+                without.ident, with.ident
+            ),
+        )),
+        None => Ok(()),
     }
-    Ok(aliases)
 }
 
-// TODO: better error handling
-fn parse_roles(enumeration: &DataEnum) -> syn::Result<HashMap<syn::Ident, Vec<Literal>>> {
-    let mut map = HashMap::<syn::Ident, Vec<Literal>>::new();
-    for variant in &enumeration.variants {
-        let mut roles = Vec::<Literal>::new();
-        for attr in &variant.attrs {
-            let Meta::List(ref list) = attr.meta else {
-                continue;
-            };
-            let mut iter = list.tokens.clone().into_iter();
-            while let Some(token) = iter.next() {
-                if let TokenTree::Ident(ref ident) = token {
-                    let SupportedArguments::Roles = ident
-                        .to_string()
-                        .parse::<SupportedArguments>()
-                        .map_err(|_| {
-                            new_error(ident, &format!("unknown variant attribute `{ident}"))
-                        })?
-                    else {
-                        continue;
-                    };
-                    if let Some(token) = iter.next() {
-                        let TokenTree::Punct(ref equal) = token else {
-                            return Err(new_error(
-                                &ident,
-                                &format!("expected punct `=` after {ident}"),
-                            ));
-                        };
-                        if equal.to_string() != "=" {
-                            return Err(new_error(
-                                &equal,
-                                &format!("expected punct `=` after {ident}"),
-                            ));
-                        }
-                        if let Some(token) = iter.next() {
-                            if let TokenTree::Group(ref group) = token {
-                                if let proc_macro2::Delimiter::Bracket = group.delimiter() {
-                                    for token in group.stream() {
-                                        if let TokenTree::Literal(literal) = token {
-                                            // TODO: is pushing this before the if let okay?
-                                            roles.push(literal);
-                                            if let Some(ref token) = iter.next() {
-                                                if let TokenTree::Punct(_) = token {
-                                                    continue;
-                                                } else {
-                                                    // return Err(new_error(
-                                                    //     &group,
-                                                    //     &format!("unexpected token"),
-                                                    // ));
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    // return Err(new_error(
-                                    //     equal,
-                                    //     &format!("expected `[` after `{equal}`"),
-                                    // ));
-                                }
-                            } else {
-                                return Err(new_error(
-                                    equal,
-                                    &format!("expected `[` after `{equal}`"),
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        map.insert(variant.ident.clone(), roles);
-    }
-    Ok(map)
+// `crate::Role::Variant` paths of the roles, `anyone` having none
+fn role_paths(roles: &[Roles]) -> Vec<proc_macro2::TokenStream> {
+    roles
+        .iter()
+        .filter_map(|role| role.variant())
+        .map(|variant| {
+            let variant = proc_macro2::Ident::new(variant, proc_macro2::Span::call_site());
+            quote! { crate::Role::#variant }
+        })
+        .collect()
 }
@@ -0,0 +1,134 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// `reference_markdown`, and `reference_json` when asked for, describing every command of `name`.
+pub(crate) fn reference_code(name: &syn::Ident, json: bool) -> TokenStream {
+    let json = json.then(|| json_code(name));
+    quote! {
+        #json
+
+        impl #name {
+            /// Every command as a Markdown reference, described in `locale`.
+            pub fn reference_markdown(locale: &str) -> String {
+                let mut markdown = String::from("# Commands\n");
+                for info in Self::COMMANDS {
+                    markdown.push_str(&format!("\n## !{}\n\n", info.name));
+                    markdown.push_str(&format!("{}\n\n", info.description_in(locale)));
+                    markdown.push_str(&format!("- usage: `{}`\n", info.usage));
+                    if !info.aliases.is_empty() {
+                        let aliases = info
+                            .aliases
+                            .iter()
+                            .map(|alias| format!("`!{alias}`"))
+                            .collect::<Vec<_>>();
+                        markdown.push_str(&format!("- aliases: {}\n", aliases.join(", ")));
+                    }
+                    markdown.push_str(&format!(
+                        "- roles: {}\n",
+                        Self::role_names(info.roles).join(", ")
+                    ));
+                    let mut cooldowns = Vec::new();
+                    if let Some(cooldown) = info.cooldown {
+                        cooldowns.push(format!("{} per user", Self::format_duration(cooldown)));
+                    }
+                    if let Some(cooldown) = info.global_cooldown {
+                        cooldowns.push(format!("{} for everyone", Self::format_duration(cooldown)));
+                    }
+                    if !cooldowns.is_empty() {
+                        let mut line = format!("- cooldown: {}", cooldowns.join(", "));
+                        if !info.cooldown_exempt.is_empty() {
+                            let exempt = info
+                                .cooldown_exempt
+                                .iter()
+                                .map(|role| role.to_string())
+                                .collect::<Vec<_>>();
+                            line.push_str(&format!(" — except {}", exempt.join(", ")));
+                        }
+                        markdown.push_str(&format!("{line}\n"));
+                    }
+                }
+                markdown
+            }
+
+            // no roles means anyone
+            fn role_names(roles: &[crate::Role]) -> Vec<String> {
+                if roles.is_empty() {
+                    return vec!["anyone".to_string()];
+                }
+                roles.iter().map(|role| role.to_string()).collect()
+            }
+
+            // `500ms`, `5s` or `1m`, as written in the `config` attribute
+            fn format_duration(duration: std::time::Duration) -> String {
+                let millis = duration.as_millis();
+                if millis % 60_000 == 0 {
+                    format!("{}m", millis / 60_000)
+                } else if millis % 1000 == 0 {
+                    format!("{}s", millis / 1000)
+                } else {
+                    format!("{millis}ms")
+                }
+            }
+        }
+    }
+}
+
+// needs `serde`, with its `derive` feature, and `serde_json` in the crate deriving
+fn json_code(name: &syn::Ident) -> TokenStream {
+    quote! {
+        impl #name {
+            /// Every command as JSON, so that room guides and dashboards match the compiled set.
+            pub fn reference_json() -> String {
+                #[derive(serde::Serialize)]
+                struct Reference {
+                    commands: Vec<CommandReference>,
+                }
+
+                #[derive(serde::Serialize)]
+                struct CommandReference {
+                    name: &'static str,
+                    aliases: &'static [&'static str],
+                    descriptions: std::collections::BTreeMap<&'static str, &'static str>,
+                    roles: Vec<String>,
+                    usage: &'static str,
+                    arguments: Vec<ArgumentReference>,
+                    cooldown_ms: Option<u128>,
+                    global_cooldown_ms: Option<u128>,
+                    cooldown_exempt: Vec<String>,
+                }
+
+                #[derive(serde::Serialize)]
+                struct ArgumentReference {
+                    name: &'static str,
+                    required: bool,
+                    allow_whitespaces: bool,
+                }
+
+                let commands = Self::COMMANDS
+                    .iter()
+                    .map(|info| CommandReference {
+                        name: info.name,
+                        aliases: info.aliases,
+                        descriptions: info.descriptions.iter().copied().collect(),
+                        roles: Self::role_names(info.roles),
+                        usage: info.usage,
+                        arguments: info
+                            .arguments
+                            .iter()
+                            .map(|argument| ArgumentReference {
+                                name: argument.name,
+                                required: argument.required,
+                                allow_whitespaces: argument.allow_whitespaces,
+                            })
+                            .collect(),
+                        cooldown_ms: info.cooldown.map(|cooldown| cooldown.as_millis()),
+                        global_cooldown_ms: info.global_cooldown.map(|cooldown| cooldown.as_millis()),
+                        cooldown_exempt: info.cooldown_exempt.iter().map(|role| role.to_string()).collect(),
+                    })
+                    .collect();
+                serde_json::to_string(&Reference { commands })
+                    .expect("the command reference is plain data")
+            }
+        }
+    }
+}
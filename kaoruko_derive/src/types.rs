@@ -1,32 +1,56 @@
-#![allow(unused)]
+use syn::{Ident, LitStr, Path};
 
-use proc_macro::TokenStream;
-use proc_macro2::Literal;
-use syn::Ident;
-
-pub type Variant = Ident;
-
-#[derive(Default)]
+/// Everything declared in the `#[config(...)]` attribute of a variant.
 pub(crate) struct Config {
-    pub(crate) alias: Option<Literal>,
-    pub(crate) description: Option<Literal>,
-    pub(crate) roles: Option<Vec<Literal>>,
-    pub(crate) string_options: Option<StringOption>,
+    pub(crate) alias: Option<LitStr>,
+    // by locale, `en` first
+    pub(crate) descriptions: Vec<(String, LitStr)>,
+    pub(crate) roles: Vec<Roles>,
+    // the `string_options` key, kept for its span
+    pub(crate) string_options: Option<(Ident, Vec<StringOption>)>,
+    pub(crate) cooldown: Cooldown,
+    // async fn called by the generated `dispatch`
+    pub(crate) handler: Option<Path>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct StringOption {
     pub(crate) required: bool,
     pub(crate) allow_whitespaces: bool,
 }
 
-#[derive(Debug, Clone)]
+/// How often a command can be used, from `cooldown`, `global_cooldown` and `cooldown_exempt`.
+#[derive(Default)]
+pub(crate) struct Cooldown {
+    // in milliseconds
+    pub(crate) per_user: Option<u64>,
+    pub(crate) global: Option<u64>,
+    pub(crate) exempt: Vec<Roles>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SupportedArguments {
     Alias,
     Description,
     Roles,
     StringOptions,
-    Unknown,
+    Cooldown,
+    GlobalCooldown,
+    CooldownExempt,
+    Handler,
+}
+
+impl SupportedArguments {
+    pub(crate) const NAMES: &[&str] = &[
+        "alias",
+        "description",
+        "roles",
+        "string_options",
+        "cooldown",
+        "global_cooldown",
+        "cooldown_exempt",
+        "handler",
+    ];
 }
 
 impl std::str::FromStr for SupportedArguments {
@@ -37,6 +61,10 @@ impl std::str::FromStr for SupportedArguments {
             "description" => Ok(Self::Description),
             "roles" => Ok(Self::Roles),
             "string_options" => Ok(Self::StringOptions),
+            "cooldown" => Ok(Self::Cooldown),
+            "global_cooldown" => Ok(Self::GlobalCooldown),
+            "cooldown_exempt" => Ok(Self::CooldownExempt),
+            "handler" => Ok(Self::Handler),
             _ => Err(()),
         }
     }
@@ -58,10 +86,40 @@ impl std::str::FromStr for SupportedStringArguments {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Roles {
     Anyone,
     Developer,
+    Trusted,
     Creator,
+    Leader,
+    Moderator,
+    Staff,
+}
+
+impl Roles {
+    pub(crate) const NAMES: &[&str] = &[
+        "anyone",
+        "developer",
+        "trusted",
+        "creator",
+        "leader",
+        "moderator",
+        "staff",
+    ];
+
+    /// The matching `crate::Role` variant, `None` for `anyone`.
+    pub(crate) fn variant(&self) -> Option<&'static str> {
+        match self {
+            Self::Anyone => None,
+            Self::Developer => Some("Developer"),
+            Self::Trusted => Some("Trusted"),
+            Self::Creator => Some("Creator"),
+            Self::Leader => Some("Leader"),
+            Self::Moderator => Some("Moderator"),
+            Self::Staff => Some("Staff"),
+        }
+    }
 }
 
 impl std::str::FromStr for Roles {
@@ -70,7 +128,11 @@ impl std::str::FromStr for Roles {
         match s {
             "anyone" => Ok(Self::Anyone),
             "developer" => Ok(Self::Developer),
+            "trusted" => Ok(Self::Trusted),
             "creator" => Ok(Self::Creator),
+            "leader" => Ok(Self::Leader),
+            "moderator" => Ok(Self::Moderator),
+            "staff" => Ok(Self::Staff),
             _ => Err(()),
         }
     }
@@ -0,0 +1,160 @@
+use kaoruko_derive::CommandParser;
+
+mod common;
+
+pub use common::Role;
+
+#[derive(CommandParser, Debug, PartialEq)]
+pub enum Command {
+    #[config(
+        alias = "c",
+        description = "search words",
+        roles = ["anyone"],
+        string_options((required, allow_whitespaces)),
+    )]
+    Search { query: String },
+    #[config(
+        description(en = "show the leaders", fr = "affiche les meilleurs"),
+        roles = ["anyone"],
+        string_options((required), ()),
+    )]
+    Top { stat: String, page: Option<u32> },
+    #[config(description = "kick the bot out", roles = ["developer"])]
+    Exit,
+}
+
+#[test]
+fn parses_arguments_into_fields() {
+    assert_eq!(
+        "c  long words ".parse::<Command>().unwrap(),
+        Command::Search {
+            query: "long words".to_string()
+        }
+    );
+    assert_eq!(
+        "top words 2".parse::<Command>().unwrap(),
+        Command::Top {
+            stat: "words".to_string(),
+            page: Some(2)
+        }
+    );
+    assert_eq!(
+        "top words".parse::<Command>().unwrap(),
+        Command::Top {
+            stat: "words".to_string(),
+            page: None
+        }
+    );
+    assert_eq!("exit".parse::<Command>().unwrap(), Command::Exit);
+}
+
+#[test]
+fn reports_usage_errors() {
+    let err = |s: &str| s.parse::<Command>().unwrap_err().to_string();
+    assert_eq!(
+        err("search"),
+        "missing argument <query> — usage: !search <query...>"
+    );
+    assert_eq!(
+        err("top words two"),
+        "invalid argument <page>: invalid digit found in string — usage: !top <stat> [page]"
+    );
+    assert_eq!(
+        err("top words 2 3"),
+        "too many arguments — usage: !top <stat> [page]"
+    );
+    assert_eq!(err("exit now"), "too many arguments — usage: !exit");
+    assert_eq!(err("xyzzy"), "unknown command");
+    // typed, for callers to word in their own language
+    assert_eq!(
+        "search"
+            .parse::<Command>()
+            .unwrap_err()
+            .downcast::<CommandError>()
+            .unwrap(),
+        CommandError::MissingArgument {
+            argument: "query",
+            usage: "!search <query...>"
+        }
+    );
+}
+
+#[test]
+fn suggests_close_commands() {
+    let err = |s: &str| s.parse::<Command>().unwrap_err();
+    assert_eq!(
+        err("serch x").to_string(),
+        "unknown command — did you mean !search?"
+    );
+    assert_eq!(
+        err("exi").to_string(),
+        "unknown command — did you mean !exit?"
+    );
+    assert_eq!(
+        err("tip").to_string(),
+        "unknown command — did you mean !top?"
+    );
+    assert_eq!(Command::suggest("cc"), ["c"]);
+    assert_eq!(Command::suggest("x"), Vec::<&str>::new());
+
+    let unknown = err("serch");
+    let unknown = unknown.downcast_ref::<UnknownCommand>().unwrap();
+    assert_eq!(unknown.name, "serch");
+    assert_eq!(unknown.suggestions, ["search"]);
+    assert_eq!(
+        Command::help_for("exut", "en").unwrap_err().to_string(),
+        "unknown command — did you mean !exit?"
+    );
+}
+
+#[test]
+fn describes_commands_by_name_or_alias() {
+    assert_eq!(
+        Command::help_for("c", "en").unwrap(),
+        "search words — usage: !search <query...> — aliases: c"
+    );
+    assert_eq!(
+        Command::help_for("exit", "en").unwrap(),
+        "kick the bot out — usage: !exit — roles: developer"
+    );
+    assert_eq!(Command::Exit.usage(), "!exit");
+    assert!(Command::help_for("nope", "en").is_err());
+}
+
+#[test]
+fn describes_commands_by_locale() {
+    let top = Command::find("top").unwrap();
+    assert_eq!(top.description, "show the leaders");
+    assert_eq!(top.description_in("fr-FR"), "affiche les meilleurs");
+    assert_eq!(top.description_in("de"), "show the leaders");
+    assert_eq!(
+        Command::help_for("top", "fr").unwrap(),
+        "affiche les meilleurs — usage: !top <stat> [page]"
+    );
+    // commands without translations are described in english
+    assert_eq!(
+        Command::Exit.info().description_in("fr"),
+        "kick the bot out"
+    );
+}
+
+#[test]
+fn lists_command_metadata() {
+    let names = Command::COMMANDS
+        .iter()
+        .map(|info| info.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["search", "top", "exit"]);
+
+    let top = Command::find("top").unwrap();
+    assert_eq!(top.arguments.len(), 2);
+    assert!(top.arguments[0].required);
+    assert_eq!(top.arguments[1].name, "page");
+    assert_eq!(Command::find("c"), Command::COMMANDS.first());
+
+    let exit = Command::Exit.info();
+    assert_eq!(exit.roles, [Role::Developer]);
+    assert!(!exit.is_permitted(&[]));
+    assert!(exit.is_permitted(&[Role::Developer]));
+    assert!(Command::find("top").unwrap().is_permitted(&[]));
+}
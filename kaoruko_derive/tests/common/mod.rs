@@ -0,0 +1,14 @@
+// the generated code expects the `Role` of the bot at the crate root, so the
+// integration tests re-export this one and the UI cases `include!` it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Developer,
+    Moderator,
+    Creator,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("{self:?}").to_lowercase())
+    }
+}
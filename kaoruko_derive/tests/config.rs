@@ -1,23 +0,0 @@
-use kaoruko_derive::CommandParser;
-
-// #[derive(CommandParser)]
-// pub enum Command {
-//     #[config(
-//         alias = "c",
-//         description = "get solve for a given query",
-//         roles = ["anyone"],
-//         string_options(
-//             (required, allow_whitespaces,)
-//         ),
-//     )]
-//     Search,
-//     #[config(
-//         description = "kicks the bot out of the room",
-//         roles = ["creator", "developer"],
-//     )]
-//     Exit,
-//     #[config(description = "kicks the bot out of the room", roles = ["anyone"])]
-//     Help,
-// }
-
-fn main() {}
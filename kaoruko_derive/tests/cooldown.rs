@@ -0,0 +1,53 @@
+use kaoruko_derive::CommandParser;
+
+mod common;
+
+pub use common::Role;
+
+#[derive(CommandParser, Debug, PartialEq)]
+pub enum Command {
+    #[config(
+        description = "search words",
+        roles = ["anyone"],
+        cooldown = "1m",
+        cooldown_exempt = ["moderator"],
+    )]
+    Search,
+    #[config(description = "start now", roles = ["anyone"], global_cooldown = "1m")]
+    Start,
+    #[config(description = "show help", roles = ["anyone"])]
+    Help,
+}
+
+#[test]
+fn declares_cooldowns() {
+    let search = Command::find("search").unwrap();
+    assert_eq!(search.cooldown, Some(std::time::Duration::from_secs(60)));
+    assert_eq!(search.global_cooldown, None);
+    assert_eq!(search.cooldown_exempt, [Role::Moderator]);
+    assert_eq!(Command::Help.info().cooldown, None);
+}
+
+#[test]
+fn refuses_commands_cooling_down() {
+    assert_eq!("search".parse_command(&[], "1").unwrap(), Command::Search);
+    assert_eq!(
+        "search".parse_command(&[], "1").unwrap_err().to_string(),
+        "wait 60s before using !search again"
+    );
+    // per user
+    assert!("search".parse_command(&[], "2").is_ok());
+    assert!("search".parse_command(&[Role::Moderator], "1").is_ok());
+
+    assert!("start".parse_command(&[], "1").is_ok());
+    assert!("start".parse_command(&[], "2").is_err());
+
+    assert!("help".parse_command(&[], "1").is_ok());
+    assert!("help".parse_command(&[], "1").is_ok());
+}
+
+#[test]
+fn does_not_start_cooldowns_on_errors() {
+    assert!("help now".parse_command(&[], "3").is_err());
+    assert!("help".parse_command(&[], "3").is_ok());
+}
@@ -0,0 +1,71 @@
+use kaoruko_derive::CommandParser;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context as TaskContext, Poll, Waker};
+
+mod common;
+
+pub use common::Role;
+
+pub struct Context {
+    pub nickname: String,
+}
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        description = "search words",
+        roles = ["anyone"],
+        string_options((required, allow_whitespaces)),
+        handler = handlers::search,
+    )]
+    Search { query: String },
+    #[config(description = "kick the bot out", roles = ["developer"], handler = handlers::exit)]
+    Exit,
+    // the field is named like its handler
+    #[config(description = "repeat a word", roles = ["anyone"], string_options((required)), handler = echo)]
+    Echo { echo: String },
+}
+
+async fn echo(_: &Context, echo: String) -> Option<String> {
+    Some(echo)
+}
+
+mod handlers {
+    use crate::Context;
+
+    pub async fn search(ctx: &Context, query: String) -> Option<String> {
+        Some(format!("{} searched {query}", ctx.nickname))
+    }
+
+    pub async fn exit(_: &Context) -> Option<String> {
+        None
+    }
+}
+
+// the handlers never wait, so a single poll completes them
+fn run<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut TaskContext::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("handler did not complete"),
+    }
+}
+
+#[test]
+fn dispatches_to_handlers() {
+    let ctx = Context {
+        nickname: "kaoruko".to_string(),
+    };
+    let cmd = "search long words".parse::<Command>().unwrap();
+    assert_eq!(
+        run(cmd.dispatch(&ctx)).as_deref(),
+        Some("kaoruko searched long words")
+    );
+    assert_eq!(run(Command::Exit.dispatch(&ctx)), None);
+    let cmd = "echo hi".parse::<Command>().unwrap();
+    assert_eq!(run(cmd.dispatch(&ctx)).as_deref(), Some("hi"));
+}
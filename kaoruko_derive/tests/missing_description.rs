@@ -1,24 +0,0 @@
-//! should fail as Search is missing description.
-// use kaoruko_derive::CommandParser;
-
-// #[derive(CommandParser)]
-// pub enum Command {
-//     #[config(
-//         alias = "c",
-//         description = "get solve for a given query",
-//         roles = ["anyone"],
-//         string_options(
-//             (name = "query", required, allow_whitespaces)
-//         ),
-//     )]
-//     Search,
-//     #[config(
-//         roles = ["creator", "developer"],
-//         description = "kicks the bot out of the room",
-//     )]
-//     Exit,
-//     #[config(description = "kicks the bot out of the room")]
-//     Help,
-// }
-
-fn main() {}
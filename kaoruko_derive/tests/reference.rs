@@ -0,0 +1,66 @@
+use kaoruko_derive::CommandParser;
+
+mod common;
+
+pub use common::Role;
+
+#[derive(CommandParser, Debug, PartialEq)]
+#[config(reference_json)]
+pub enum Command {
+    #[config(
+        alias = "c",
+        description(en = "search \"words\"", fr = "cherche des mots"),
+        roles = ["anyone"],
+        cooldown = "5s",
+        global_cooldown = "500ms",
+        cooldown_exempt = ["moderator"],
+        string_options((allow_whitespaces)),
+    )]
+    Search { query: Option<String> },
+    #[config(description = "kick the bot out", roles = ["developer"])]
+    Exit,
+}
+
+#[test]
+fn describes_commands_as_json() {
+    assert_eq!(
+        Command::reference_json(),
+        concat!(
+            r#"{"commands":["#,
+            r#"{"name":"search","aliases":["c"],"#,
+            r#""descriptions":{"en":"search \"words\"","fr":"cherche des mots"},"#,
+            r#""roles":["anyone"],"usage":"!search [query...]","#,
+            r#""arguments":[{"name":"query","required":false,"allow_whitespaces":true}],"#,
+            r#""cooldown_ms":5000,"global_cooldown_ms":500,"cooldown_exempt":["moderator"]},"#,
+            r#"{"name":"exit","aliases":[],"descriptions":{"en":"kick the bot out"},"#,
+            r#""roles":["developer"],"usage":"!exit","arguments":[],"#,
+            r#""cooldown_ms":null,"global_cooldown_ms":null,"cooldown_exempt":[]}"#,
+            "]}"
+        )
+    );
+}
+
+#[test]
+fn describes_commands_as_markdown() {
+    assert_eq!(
+        Command::reference_markdown("fr"),
+        "# Commands
+
+## !search
+
+cherche des mots
+
+- usage: `!search [query...]`
+- aliases: `!c`
+- roles: anyone
+- cooldown: 5s per user, 500ms for everyone — except moderator
+
+## !exit
+
+kick the bot out
+
+- usage: `!exit`
+- roles: developer
+"
+    );
+}
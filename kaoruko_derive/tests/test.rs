@@ -1,8 +1,17 @@
 #[test]
 fn tests() {
     let tests = trybuild::TestCases::new();
-    tests.pass("tests/parse.rs");
-    tests.pass("tests/config.rs");
-    tests.compile_fail("tests/missing_description.rs");
-    tests.compile_fail("tests/missing_variants.rs");
+    tests.pass("tests/ui/parse.rs");
+    tests.pass("tests/ui/config.rs");
+    tests.compile_fail("tests/ui/missing_description.rs");
+    tests.compile_fail("tests/ui/missing_variants.rs");
+    tests.compile_fail("tests/ui/invalid_cooldown.rs");
+    tests.compile_fail("tests/ui/unknown_key.rs");
+    tests.compile_fail("tests/ui/unknown_enum_key.rs");
+    tests.compile_fail("tests/ui/unknown_role.rs");
+    tests.compile_fail("tests/ui/wrong_value_type.rs");
+    tests.compile_fail("tests/ui/duplicate_alias.rs");
+    tests.compile_fail("tests/ui/alias_shadows_name.rs");
+    tests.compile_fail("tests/ui/missing_handler.rs");
+    tests.compile_fail("tests/ui/missing_english_description.rs");
 }
@@ -0,0 +1,11 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(description = "kicks the bot out of the room", roles = ["developer"])]
+    Exit,
+    #[config(alias = "exit", description = "start the game now", roles = ["developer"])]
+    StartNow,
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use kaoruko_derive::CommandParser;
+
+include!("../common/mod.rs");
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        alias = "c",
+        description = "get solve for a given query",
+        roles = ["anyone"],
+        string_options(
+            (required, allow_whitespaces,)
+        ),
+    )]
+    Search { query: String },
+    #[config(
+        description = "kicks the bot out of the room",
+        roles = ["creator", "developer"],
+    )]
+    Exit,
+    #[config(
+        description = "get help for a command",
+        roles = ["anyone"],
+        string_options((), ()),
+    )]
+    Help {
+        command: Option<String>,
+        page: Option<u32>,
+    },
+}
+
+fn main() {}
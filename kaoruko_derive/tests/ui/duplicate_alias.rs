@@ -0,0 +1,11 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(alias = "s", description = "get solve for a given query", roles = ["anyone"])]
+    Search,
+    #[config(alias = "s", description = "display your stats", roles = ["anyone"])]
+    Stats,
+}
+
+fn main() {}
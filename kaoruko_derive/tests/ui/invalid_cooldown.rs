@@ -0,0 +1,11 @@
+use kaoruko_derive::CommandParser;
+
+include!("../common/mod.rs");
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(description = "search words", roles = ["anyone"], cooldown = "5 seconds")]
+    Search,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+//! should fail as Help is missing description.
+use kaoruko_derive::CommandParser;
+
+include!("../common/mod.rs");
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        alias = "c",
+        description = "get solve for a given query",
+        roles = ["anyone"],
+        string_options(
+            (required, allow_whitespaces)
+        ),
+    )]
+    Search { query: String },
+    #[config(
+        roles = ["creator", "developer"],
+        description = "kicks the bot out of the room",
+    )]
+    Exit,
+    #[config(roles = ["anyone"])]
+    Help,
+}
+
+fn main() {}
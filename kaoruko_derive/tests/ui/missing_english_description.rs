@@ -0,0 +1,9 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(description(fr = "affiche les meilleurs"), roles = ["anyone"])]
+    Top,
+}
+
+fn main() {}
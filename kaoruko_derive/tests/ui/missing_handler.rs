@@ -0,0 +1,11 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(description = "start the game now", roles = ["developer"], handler = start_now)]
+    StartNow,
+    #[config(description = "kicks the bot out of the room", roles = ["developer"])]
+    Exit,
+}
+
+fn main() {}
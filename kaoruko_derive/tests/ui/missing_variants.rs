@@ -1,6 +1,6 @@
 use kaoruko_derive::CommandParser;
 
-// #[derive(CommandParser)]
-// pub enum Test {}
+#[derive(CommandParser)]
+pub enum Test {}
 
 fn main() {}
@@ -0,0 +1,17 @@
+use kaoruko_derive::CommandParser;
+
+include!("../common/mod.rs");
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        description = "use for searching words",
+        roles = ["anyone"],
+        string_options((allow_whitespaces)),
+    )]
+    Search { query: Option<String> },
+    #[config(description = "kick bot out of room", roles = ["developer"])]
+    Exit,
+}
+
+fn main() {}
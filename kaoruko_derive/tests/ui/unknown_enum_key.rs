@@ -0,0 +1,10 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+#[config(reference)]
+pub enum Command {
+    #[config(description = "get solve for a given query", roles = ["anyone"])]
+    Search,
+}
+
+fn main() {}
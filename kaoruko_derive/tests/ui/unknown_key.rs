@@ -0,0 +1,13 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        description = "get solve for a given query",
+        roles = ["anyone"],
+        aliases = "c",
+    )]
+    Search,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(
+        description = "kicks the bot out of the room",
+        roles = ["developer", "admin"],
+    )]
+    Exit,
+}
+
+fn main() {}
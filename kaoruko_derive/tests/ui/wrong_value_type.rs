@@ -0,0 +1,9 @@
+use kaoruko_derive::CommandParser;
+
+#[derive(CommandParser)]
+pub enum Command {
+    #[config(description = "kicks the bot out of the room", roles = "developer")]
+    Exit,
+}
+
+fn main() {}
@@ -0,0 +1,233 @@
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+use crate::bot::BotHandle;
+use crate::config::ApiConfig;
+use crate::http::{read_request, write_response, Request, Response};
+use crate::i18n::Message;
+use crate::types::WordStrategy;
+
+/// Room driven by the api.
+#[derive(Clone)]
+pub struct Room {
+    pub code: &'static str,
+    pub bot: Arc<BotHandle>,
+    // halts the bot, as `!exit` does
+    pub notifier: Arc<Notify>,
+}
+
+#[derive(Deserialize)]
+struct ChatBody {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct StrategyBody {
+    strategy: WordStrategy,
+}
+
+/// Serves the control api on the configured address until the process exits.
+pub async fn serve(config: ApiConfig, rooms: Vec<Room>) -> anyhow::Result<()> {
+    let address = config.address;
+    if !address.ip().is_loopback() {
+        return Err(anyhow!(
+            "refusing to serve the api on non local address {address}"
+        ));
+    }
+    if config.token.is_empty() {
+        return Err(anyhow!("refusing to serve the api without a token"));
+    }
+    let listener = TcpListener::bind(address).await?;
+    let rooms = Arc::new(rooms);
+    let token = Arc::new(config.token);
+    tracing::info!("Serving control api at http://{address}");
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let rooms = Arc::clone(&rooms);
+        let token = Arc::clone(&token);
+        tokio::spawn(async move {
+            let response = match read_request(&mut stream).await {
+                Ok(request) => match authorize(&request, &token) {
+                    Ok(()) => route(request, &rooms).await,
+                    Err(response) => response,
+                },
+                Err(err) => error(400, err.to_string()),
+            };
+            if let Err(err) = write_response(&mut stream, response).await {
+                tracing::warn!("failed to answer api request: {err}");
+            }
+        });
+    }
+}
+
+// browsers send an `Origin` with cross site requests, and cannot send a bearer token without
+// asking first, so web pages opened by the operator cannot drive the bot
+fn authorize(request: &Request, token: &str) -> Result<(), Response> {
+    if request.header("origin").is_some() {
+        return Err(error(403, "cross origin requests are not allowed"));
+    }
+    let bearer = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !same(bearer.as_bytes(), token.as_bytes()) {
+        return Err(error(401, "missing or invalid bearer token"));
+    }
+    let json = request
+        .header("content-type")
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("application/json"));
+    if request.method != "GET" && !json {
+        return Err(error(415, "expected content-type: application/json"));
+    }
+    Ok(())
+}
+
+// compares without stopping at the first difference, so the token cannot be guessed by timing
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+async fn route(request: Request, rooms: &[Room]) -> Response {
+    let segments = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    let method = request.method.as_str();
+    match segments.as_slice() {
+        ["rooms"] if method == "GET" => {
+            let mut list = Vec::new();
+            for room in rooms {
+                list.push(describe(room).await);
+            }
+            ok(json!(list))
+        }
+        ["rooms", code, rest @ ..] => {
+            let Some(room) = rooms.iter().find(|room| room.code == *code) else {
+                return error(404, format!("no active room with code {code}"));
+            };
+            room_route(method, rest, &request.body, room).await
+        }
+        _ => error(404, "not found"),
+    }
+}
+
+async fn room_route(method: &str, segments: &[&str], body: &[u8], room: &Room) -> Response {
+    let bot = &room.bot;
+    match (method, segments) {
+        ("GET", []) => ok(describe(room).await),
+        ("DELETE", []) => {
+            bot.set_chat(bot.get_locale().await.text(Message::Sayonara))
+                .await;
+            room.notifier.notify_one();
+            ok(json!({ "code": room.code, "closed": true }))
+        }
+        ("GET", ["syllable"]) => ok(json!({ "syllable": bot.get_syllable().await })),
+        ("GET", ["players"]) => {
+            let players = bot
+                .get_players()
+                .await
+                .into_iter()
+                .map(|(peer_id, stats)| json!({ "peer_id": peer_id, "stats": stats }))
+                .collect::<Vec<_>>();
+            ok(json!(players))
+        }
+        ("POST", ["chat"]) => match serde_json::from_slice::<ChatBody>(body) {
+            Ok(ChatBody { message }) => {
+                bot.set_chat(message).await;
+                ok(json!({ "sent": true }))
+            }
+            Err(err) => error(400, format!("invalid body: {err}")),
+        },
+        ("POST", ["start"]) => {
+            bot.start_round_now().await;
+            ok(json!({ "started": true }))
+        }
+        ("GET", ["strategy"]) => ok(json!({ "strategy": bot.get_strategy().await })),
+        ("PUT", ["strategy"]) => match serde_json::from_slice::<StrategyBody>(body) {
+            Ok(StrategyBody { strategy }) => {
+                bot.set_strategy(strategy).await;
+                ok(json!({ "strategy": strategy }))
+            }
+            Err(err) => error(400, format!("invalid body: {err}")),
+        },
+        (_, [] | ["syllable" | "players" | "chat" | "start" | "strategy"]) => {
+            error(405, "method not allowed")
+        }
+        _ => error(404, "not found"),
+    }
+}
+
+async fn describe(room: &Room) -> Value {
+    json!({
+        "code": room.code,
+        "url": format!("https://jklm.fun/{}", room.code),
+        "syllable": room.bot.get_syllable().await,
+        "strategy": room.bot.get_strategy().await,
+        "players": room.bot.get_players().await.len(),
+    })
+}
+
+fn ok(value: Value) -> Response {
+    Response::new(200, "application/json", value.to_string())
+}
+
+fn error(status: u16, message: impl Into<String>) -> Response {
+    let message = message.into();
+    Response::new(
+        status,
+        "application/json",
+        json!({ "error": message }).to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: "/rooms/ABCD/chat".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    fn status(request: &Request) -> u16 {
+        authorize(request, "secret").map_or_else(|response| response.status, |()| 200)
+    }
+
+    #[test]
+    fn authorizes_requests() {
+        let auth = ("Authorization", "Bearer secret");
+        let json = ("Content-Type", "application/json; charset=utf-8");
+        assert_eq!(status(&request("GET", &[auth])), 200);
+        assert_eq!(status(&request("POST", &[auth, json])), 200);
+        assert_eq!(status(&request("POST", &[json])), 401);
+        assert_eq!(
+            status(&request(
+                "POST",
+                &[("authorization", "Bearer secreT"), json]
+            )),
+            401
+        );
+        // simple requests browsers send without asking
+        assert_eq!(
+            status(&request("POST", &[auth, ("Content-Type", "text/plain")])),
+            415
+        );
+        assert_eq!(
+            status(&request("GET", &[auth, ("Origin", "https://example.com")])),
+            403
+        );
+    }
+}
@@ -1,24 +1,38 @@
-use regex::Regex;
 use rust_socketio::asynchronous::Client;
-use std::collections::HashMap;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 
-use crate::types::PlayerStats;
+use crate::chat::ChatQueue;
+use crate::config::RoomConfig;
+use crate::discord::DiscordBridge;
+use crate::i18n::{Locale, Message};
+use crate::metrics::METRICS;
+use crate::moderation::BanList;
+use crate::perks::{Perk, PerksConfig};
+use crate::query::{count_syllables, Query, SearchError};
+use crate::roles::{outranks, Role};
+use crate::rules::Rules;
+use crate::stats::{format_leaders, format_rank, Category, GameRecord, StatsStore, Window};
+use crate::summary::GameSummary;
+use crate::types::{Auth, Constants, PlayerStats, Setup, WordStrategy};
 use crate::utils::shuffle;
 use crate::Dictionary;
 
 struct Bot {
     // for receiving values from the associated sender
     receiver: mpsc::Receiver<BotMessage>,
-    // english dictionary
-    dictionary: Dictionary,
+    // english dictionary, shared with the searches running on the blocking pool
+    dictionary: Arc<Dictionary>,
+    // words of the dictionary jklm refused, skipped rather than removed to keep it shared
+    removed: HashSet<String>,
     // unique id of bot inside room
     self_peer_id: AtomicU64,
-    // the user who created the room
-    room_creator: String,
-    // a list of words used in the game
-    used_words: Vec<String>,
+    // words used in the current round
+    used_words: HashSet<String>,
     // dynamically changing words as per typing
     player_word: String,
     // current active syllable in game
@@ -29,11 +43,87 @@ struct Bot {
     players: HashMap<u64, PlayerStats>,
     // room socket
     room_socket: Option<Client>,
+    // every message of the bot goes through it
+    chat: ChatQueue,
+    // code of the room the bot is playing in
+    room_code: String,
+    // stats of finished games
+    stats: StatsStore,
+    // discord channel the room is mirrored to
+    discord: Option<DiscordBridge>,
+    // how words are picked for the bot's turns
+    strategy: WordStrategy,
+    // language of the room
+    locale: Locale,
+    // marks chat messages as commands
+    prefix: String,
+    // how perks are counted and announced
+    perks: PerksConfig,
+    announce_perks: bool,
+    // last search of every user, for `!more`
+    cursors: HashMap<String, Cursor>,
+    // only the leader can change the rules
+    leader_peer_id: Option<u64>,
+    // limits of the game, from its setup
+    constants: Option<Constants>,
+    // `seating` between rounds
+    milestone: String,
+    rules_locked: bool,
+    // chatters kept out of the room
+    bans: BanList,
+    // everyone in the room, by peer id
+    chatters: HashMap<u64, Present>,
+    // chatters whose commands are ignored, until when
+    muted: HashMap<u64, Instant>,
 }
 
+/// A chatter in the room, as far as moderation is concerned.
+struct Present {
+    nickname: String,
+    auth: Option<Auth>,
+    roles: Vec<Role>,
+}
+
+/// A search being paged through.
+#[derive(Clone)]
+struct Cursor {
+    query: Query,
+    // current syllable when the search started
+    pattern: String,
+    // removed words, and played ones for `unused`
+    skipped: HashSet<String>,
+    page: usize,
+    expires: Instant,
+}
+
+// where finished games are persisted
+const STATS_PATH: &str = "stats.jsonl";
+// where the ban list is persisted
+const BANS_PATH: &str = "bans.json";
+// directory holding one json summary per game
+const SUMMARIES_DIR: &str = "games";
+// how many players a leaderboard shows
+const LEADERBOARD_SIZE: usize = 5;
+// definitions of the words of the dictionary, for `!define`, none ship with the bot
+//
+// a json object from word to definition, such as `{"bomb": "an explosive device"}`, words
+// being looked up whatever their case
+const DEFINITIONS_PATH: &str = "src/dictionaries/definitions.json";
+// time a `!search` may scan the dictionary for
+const SEARCH_BUDGET: Duration = Duration::from_millis(500);
+// results shown by a `!search` or `!more`
+const RESULTS_PER_PAGE: usize = 15;
+// time a search can be continued with `!more` for
+const CURSOR_TIMEOUT: Duration = Duration::from_secs(120);
+
 enum BotMessage {
     GetWords {
-        query: String,
+        query: Query,
+        user: String,
+        respond_to: oneshot::Sender<String>,
+    },
+    GetMoreWords {
+        user: String,
         respond_to: oneshot::Sender<String>,
     },
     GetWord {
@@ -46,12 +136,6 @@ enum BotMessage {
     GetPeerId {
         respond_to: oneshot::Sender<u64>,
     },
-    SetRoomCreator {
-        creator: String,
-    },
-    GetRoomCreator {
-        respond_to: oneshot::Sender<String>,
-    },
     AddWord {
         word: String,
     },
@@ -92,61 +176,197 @@ enum BotMessage {
         peer_id: u64,
         respond_to: oneshot::Sender<Option<PlayerStats>>,
     },
+    GetPlayers {
+        respond_to: oneshot::Sender<Vec<(u64, PlayerStats)>>,
+    },
+    FindPlayer {
+        nickname: String,
+        respond_to: oneshot::Sender<Option<PlayerStats>>,
+    },
     SetRoomSocket {
         socket: Client,
     },
     SetChat {
         message: String,
     },
+    Announce {
+        message: String,
+    },
     UpdateLives {
         peer_id: u64,
         respond_to: oneshot::Sender<u64>,
     },
+    RecordGame {
+        winner: Option<String>,
+    },
+    GetLeaders {
+        category: Category,
+        window: Window,
+        respond_to: oneshot::Sender<String>,
+    },
+    GetRank {
+        nickname: String,
+        category: Category,
+        window: Window,
+        respond_to: oneshot::Sender<String>,
+    },
+    SetDiscord {
+        bridge: DiscordBridge,
+    },
+    SetStrategy {
+        strategy: WordStrategy,
+    },
+    GetStrategy {
+        respond_to: oneshot::Sender<WordStrategy>,
+    },
+    GetLocale {
+        respond_to: oneshot::Sender<Locale>,
+    },
+    SetPrefix {
+        prefix: String,
+    },
+    GetPrefix {
+        respond_to: oneshot::Sender<String>,
+    },
+    GetDefinition {
+        word: String,
+        respond_to: oneshot::Sender<Option<String>>,
+    },
+    SetSetup {
+        setup: Setup,
+    },
+    SetLeader {
+        peer_id: u64,
+    },
+    SetMilestone {
+        name: String,
+        rules_locked: bool,
+    },
+    SetRules {
+        rules: Rules,
+        respond_to: oneshot::Sender<String>,
+    },
+    AddChatter {
+        peer_id: u64,
+        nickname: String,
+        auth: Option<Auth>,
+        roles: Vec<Role>,
+        respond_to: oneshot::Sender<bool>,
+    },
+    SetChatterRoles {
+        peer_id: u64,
+        roles: Vec<Role>,
+    },
+    RemoveChatter {
+        peer_id: u64,
+    },
+    Kick {
+        nickname: String,
+        roles: Vec<Role>,
+        respond_to: oneshot::Sender<String>,
+    },
+    Ban {
+        nickname: String,
+        by: String,
+        roles: Vec<Role>,
+        respond_to: oneshot::Sender<String>,
+    },
+    Unban {
+        nickname: String,
+        respond_to: oneshot::Sender<String>,
+    },
+    GetBans {
+        respond_to: oneshot::Sender<String>,
+    },
+    Mute {
+        nickname: String,
+        duration: Duration,
+        roles: Vec<Role>,
+        respond_to: oneshot::Sender<String>,
+    },
+    IsMuted {
+        peer_id: u64,
+        respond_to: oneshot::Sender<bool>,
+    },
 }
 
 impl Bot {
     // prpogate everything up from here and ignore at a single place.
-    fn new(receiver: mpsc::Receiver<BotMessage>) -> Self {
-        let dictionary = std::fs::read_to_string("src/dictionaries/english.json").unwrap();
-        let mut dictionary = serde_json::from_str::<Dictionary>(&dictionary).unwrap();
-        shuffle(&mut dictionary.dictionary);
-        Self {
+    fn new(
+        receiver: mpsc::Receiver<BotMessage>,
+        room_code: String,
+        room: RoomConfig,
+        perks: PerksConfig,
+        chat: ChatQueue,
+        dictionary: Dictionary,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
             receiver,
-            dictionary,
+            dictionary: Arc::new(dictionary),
+            removed: HashSet::new(),
             self_peer_id: AtomicU64::default(),
-            room_creator: String::default(),
-            used_words: Vec::<String>::new(),
+            used_words: HashSet::new(),
             player_word: String::default(),
             syllable: String::default(),
             game_socket: None,
             players: HashMap::default(),
             room_socket: None,
-        }
+            chat,
+            room_code,
+            stats: StatsStore::load(STATS_PATH)?,
+            discord: None,
+            strategy: WordStrategy::default(),
+            locale: room.language,
+            prefix: room.prefix,
+            perks,
+            announce_perks: room.announce_perks,
+            cursors: HashMap::default(),
+            leader_peer_id: None,
+            constants: None,
+            milestone: String::default(),
+            rules_locked: false,
+            bans: BanList::load(BANS_PATH)?,
+            chatters: HashMap::default(),
+            muted: HashMap::default(),
+        })
     }
     async fn handle_message(&mut self, msg: BotMessage) {
         match msg {
-            BotMessage::GetWords { query, respond_to } => {
-                let words = self.dictionary.dictionary.get_mut();
-                if let Ok(re) = Regex::new(&query) {
-                    let result = words.iter().filter(|w| re.is_match(w)).collect::<Vec<_>>();
-                    let fifteen = if result.len() > 15 { 15 } else { result.len() };
-                    let fifteen = result[0..fifteen]
-                        .iter()
-                        .map(|&word| word.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    if fifteen.is_empty() {
-                        respond_to
-                            .send(format!("No result found for: {query}"))
-                            .unwrap();
-                    } else {
+            BotMessage::GetWords {
+                query,
+                user,
+                respond_to,
+            } => {
+                let now = Instant::now();
+                self.cursors.retain(|_, cursor| cursor.expires > now);
+                let mut skipped = self.removed.clone();
+                if query.unused {
+                    skipped.extend(self.used_words.iter().cloned());
+                }
+                let cursor = Cursor {
+                    page: query.page.unwrap_or(1),
+                    pattern: self.syllable.clone(),
+                    skipped,
+                    query,
+                    expires: now + CURSOR_TIMEOUT,
+                };
+                self.cursors.insert(user, cursor.clone());
+                self.search(cursor, respond_to);
+            }
+            BotMessage::GetMoreWords { user, respond_to } => {
+                let now = Instant::now();
+                match self.cursors.get_mut(&user) {
+                    Some(cursor) if cursor.expires > now => {
+                        cursor.page = cursor.page.saturating_add(1);
+                        cursor.expires = now + CURSOR_TIMEOUT;
+                        let cursor = cursor.clone();
+                        self.search(cursor, respond_to);
+                    }
+                    _ => {
                         respond_to
-                            .send(format!("results({}): {fifteen}", result.len()))
+                            .send(self.locale.text(Message::NoSearch))
                             .unwrap();
-                        shuffle(&mut self.dictionary.dictionary);
                     }
-                } else {
-                    respond_to.send(format!("too expensive regex")).unwrap();
                 }
             }
             BotMessage::SetPeerId { peer_id } => {
@@ -157,26 +377,28 @@ impl Bot {
                     .send(self.self_peer_id.load(Ordering::Relaxed))
                     .unwrap();
             }
-            BotMessage::SetRoomCreator { creator } => {
-                self.room_creator = creator;
-            }
-            BotMessage::GetRoomCreator { respond_to } => {
-                respond_to.send(self.room_creator.clone()).unwrap();
-            }
             BotMessage::GetWord {
                 syllable,
                 respond_to,
             } => {
-                let Dictionary { dictionary, .. } = &self.dictionary;
-                for word in dictionary.borrow().iter() {
-                    if word.contains(&syllable) && !self.used_words.contains(word) {
-                        respond_to.send(word.clone()).unwrap();
-                        break;
-                    }
+                let started = Instant::now();
+                let mut candidates = self.dictionary.dictionary.iter().filter(|word| {
+                    word.contains(&syllable)
+                        && !self.used_words.contains(*word)
+                        && !self.removed.contains(*word)
+                });
+                let word = match self.strategy {
+                    WordStrategy::Random => candidates.next(),
+                    WordStrategy::Shortest => candidates.min_by_key(|word| word.len()),
+                    WordStrategy::Longest => candidates.max_by_key(|word| word.len()),
+                };
+                if let Some(word) = word {
+                    respond_to.send(word.clone()).unwrap();
                 }
+                METRICS.word_lookup.observe(started.elapsed());
             }
             BotMessage::AddWord { word } => {
-                self.used_words.push(word);
+                self.used_words.insert(word);
             }
             BotMessage::IsUsedWord { word, respond_to } => {
                 respond_to.send(self.used_words.contains(&word)).unwrap();
@@ -194,13 +416,13 @@ impl Bot {
                 respond_to.send(self.syllable.clone()).unwrap();
             }
             BotMessage::RemoveWord { word } => {
-                let mut dict = self.dictionary.dictionary.borrow_mut();
-                if let Some(index) = dict.iter().position(|w| w == &word) {
-                    dict.remove(index);
-                }
+                self.removed.insert(word);
             }
             BotMessage::SetGameSocket { socket } => {
-                self.game_socket = Some(socket);
+                // joining the room again after a reconnect opens a new game socket
+                if let Some(previous) = self.game_socket.replace(socket) {
+                    let _ = previous.disconnect().await;
+                }
             }
             BotMessage::StartRoundNow => {
                 self.game_socket
@@ -216,27 +438,22 @@ impl Bot {
                 word,
             } => {
                 let player = self.players.get_mut(&peer_id).unwrap();
-                let client = self.room_socket.as_ref().unwrap();
-                let mut perk = format!("{nickname} has placed");
-                let mut considerable = false;
-                if word.len() >= 20 {
+                let mut perks = Vec::new();
+                if self.perks.is_long(&word) {
                     player.longs += 1;
-                    considerable = true;
-                    perk.push_str(format!(" a long ({}) —", player.longs).as_str());
+                    perks.push((Perk::Long, player.longs));
                 }
                 if word.contains("-") {
                     player.hyphens += 1;
-                    considerable = true;
-                    perk.push_str(format!(" a hyphen ({}) —", player.hyphens).as_str());
+                    perks.push((Perk::Hyphen, player.hyphens));
                 }
                 if self.dictionary.sn.contains(&word) {
                     player.subs += 1;
-                    considerable = true;
-                    perk.push_str(format!(" a sn ({}) —", player.subs).as_str());
+                    perks.push((Perk::Sub, player.subs));
                 }
                 if word.contains(" ") {
                     let word = word.split(" ").collect::<Vec<_>>();
-                    let words = self.dictionary.dictionary.get_mut();
+                    let words = &self.dictionary.dictionary;
                     let mut contains = true;
                     for w in word {
                         if !words.contains(&w.to_string()) {
@@ -246,17 +463,21 @@ impl Bot {
                     }
                     if contains {
                         player.multi += 1;
-                        considerable = true;
-                        perk.push_str(format!(" a multi ({}) —", player.multi).as_str());
+                        perks.push((Perk::Multi, player.multi));
                     }
                 }
                 player.words += 1;
-                if perk.ends_with("—") {
-                    perk = perk.replace(" —", "");
+                if word.len() > player.longest_word.len() {
+                    player.longest_word = word.clone();
+                }
+                if !self.announce_perks {
+                    return;
                 }
-                perk.push_str(format!(": {word}").as_str());
-                if considerable {
-                    client.emit("chat", perk).await.unwrap();
+                if let Some(announcement) =
+                    self.perks
+                        .announcement(self.locale, &nickname, &word, &perks)
+                {
+                    self.chat.announce(announcement);
                 }
             }
             BotMessage::AddPlayer {
@@ -271,19 +492,38 @@ impl Bot {
                 peer_id,
                 respond_to,
             } => {
-                let player = self.players.get(&peer_id).map(|p| p.clone());
+                let player = self.players.get(&peer_id).cloned();
+                respond_to.send(player).unwrap();
+            }
+            BotMessage::GetPlayers { respond_to } => {
+                let mut players = self
+                    .players
+                    .iter()
+                    .map(|(peer_id, p)| (*peer_id, p.clone()))
+                    .collect::<Vec<_>>();
+                players.sort_by_key(|(peer_id, _)| *peer_id);
+                respond_to.send(players).unwrap();
+            }
+            BotMessage::FindPlayer {
+                nickname,
+                respond_to,
+            } => {
+                let player = self
+                    .players
+                    .values()
+                    .find(|p| p.nickname.eq_ignore_ascii_case(&nickname))
+                    .cloned();
                 respond_to.send(player).unwrap();
             }
             BotMessage::SetRoomSocket { socket } => {
+                self.chat.set_socket(socket.clone());
                 self.room_socket = Some(socket);
             }
             BotMessage::SetChat { message } => {
-                let _ = self
-                    .room_socket
-                    .as_ref()
-                    .unwrap()
-                    .emit("chat", message)
-                    .await;
+                self.chat.say(message);
+            }
+            BotMessage::Announce { message } => {
+                self.chat.announce(message);
             }
             BotMessage::UpdateLives {
                 peer_id,
@@ -293,6 +533,419 @@ impl Bot {
                 player.lives += 1;
                 respond_to.send(player.lives).unwrap();
             }
+            BotMessage::RecordGame { winner } => {
+                if self.players.is_empty() {
+                    return;
+                }
+                let game = GameRecord::new(&self.room_code, self.players.values());
+                let summary = GameSummary::new(&game, winner);
+                self.chat.say(summary.to_chat(self.locale));
+                if let Some(discord) = self.discord.as_ref() {
+                    discord.send(summary.to_chat(self.locale));
+                }
+                if let Err(err) = summary.write(SUMMARIES_DIR) {
+                    tracing::error!("failed to write game summary: {err}");
+                }
+                if let Err(err) = self.stats.record(game) {
+                    tracing::error!("failed to save stats: {err}");
+                }
+                self.players.clear();
+            }
+            BotMessage::GetLeaders {
+                category,
+                window,
+                respond_to,
+            } => {
+                let ongoing = GameRecord::new(&self.room_code, self.players.values());
+                let room =
+                    self.stats
+                        .leaders(category, window, Some(&self.room_code), Some(&ongoing));
+                let overall = self.stats.leaders(category, window, None, Some(&ongoing));
+                respond_to
+                    .send(self.locale.format(
+                        Message::Leaders,
+                        &[
+                            ("category", &category),
+                            ("window", &window),
+                            (
+                                "room",
+                                &format_leaders(&room, LEADERBOARD_SIZE, self.locale),
+                            ),
+                            (
+                                "overall",
+                                &format_leaders(&overall, LEADERBOARD_SIZE, self.locale),
+                            ),
+                        ],
+                    ))
+                    .unwrap();
+            }
+            BotMessage::GetRank {
+                nickname,
+                category,
+                window,
+                respond_to,
+            } => {
+                let ongoing = GameRecord::new(&self.room_code, self.players.values());
+                let room =
+                    self.stats
+                        .leaders(category, window, Some(&self.room_code), Some(&ongoing));
+                let overall = self.stats.leaders(category, window, None, Some(&ongoing));
+                respond_to
+                    .send(self.locale.format(
+                        Message::Rank,
+                        &[
+                            ("nickname", &nickname),
+                            ("category", &category),
+                            ("window", &window),
+                            ("room", &format_rank(&room, &nickname, self.locale)),
+                            ("overall", &format_rank(&overall, &nickname, self.locale)),
+                        ],
+                    ))
+                    .unwrap();
+            }
+            BotMessage::SetDiscord { bridge } => {
+                self.discord = Some(bridge);
+            }
+            BotMessage::SetStrategy { strategy } => {
+                self.strategy = strategy;
+            }
+            BotMessage::GetStrategy { respond_to } => {
+                respond_to.send(self.strategy).unwrap();
+            }
+            BotMessage::GetLocale { respond_to } => {
+                respond_to.send(self.locale).unwrap();
+            }
+            BotMessage::SetPrefix { prefix } => {
+                self.prefix = prefix;
+            }
+            BotMessage::GetPrefix { respond_to } => {
+                respond_to.send(self.prefix.clone()).unwrap();
+            }
+            BotMessage::SetSetup { setup } => {
+                self.leader_peer_id = Some(setup.leader_peer_id);
+                self.constants = Some(setup.constants);
+                self.milestone = setup.milestone.name;
+                self.rules_locked = setup.milestone.rules_locked;
+            }
+            BotMessage::SetLeader { peer_id } => {
+                self.leader_peer_id = Some(peer_id);
+            }
+            BotMessage::SetMilestone { name, rules_locked } => {
+                // words can be played again in the next round
+                if name == "seating" {
+                    self.used_words.clear();
+                }
+                self.milestone = name;
+                self.rules_locked = rules_locked;
+            }
+            BotMessage::SetRules { rules, respond_to } => {
+                let reply = match self.check_rules(&rules) {
+                    Err(reply) => reply,
+                    Ok(()) => {
+                        let socket = self.game_socket.as_ref().unwrap();
+                        // locked rules cannot be changed, they are locked back afterwards
+                        if self.rules_locked {
+                            let _ = socket.emit("setRulesLocked", json!(false)).await;
+                        }
+                        let _ = socket.emit("setRules", json!(rules)).await;
+                        if self.rules_locked {
+                            let _ = socket.emit("setRulesLocked", json!(true)).await;
+                        }
+                        self.locale
+                            .format(Message::RulesChanged, &[("rules", &rules)])
+                    }
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::GetDefinition { word, respond_to } => {
+                let definition = self.dictionary.definitions.get(&word.to_lowercase());
+                respond_to.send(definition.cloned()).unwrap();
+            }
+            BotMessage::AddChatter {
+                peer_id,
+                nickname,
+                auth,
+                roles,
+                respond_to,
+            } => {
+                let banned = self.bans.is_banned(&nickname, auth.as_ref());
+                if banned {
+                    tracing::info!("kicking {nickname}, who is banned");
+                    self.kick(peer_id).await;
+                } else {
+                    self.chatters.insert(
+                        peer_id,
+                        Present {
+                            nickname,
+                            auth,
+                            roles,
+                        },
+                    );
+                }
+                respond_to.send(banned).unwrap();
+            }
+            BotMessage::SetChatterRoles { peer_id, roles } => {
+                if let Some(chatter) = self.chatters.get_mut(&peer_id) {
+                    chatter.roles = roles;
+                }
+            }
+            BotMessage::RemoveChatter { peer_id } => {
+                self.chatters.remove(&peer_id);
+                self.muted.remove(&peer_id);
+            }
+            BotMessage::Kick {
+                nickname,
+                roles,
+                respond_to,
+            } => {
+                let reply = match self.target(&nickname, &roles) {
+                    Err(reply) => reply,
+                    Ok(peer_id) => {
+                        self.kick(peer_id).await;
+                        self.locale
+                            .format(Message::Kicked, &[("nickname", &nickname)])
+                    }
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::Ban {
+                nickname,
+                by,
+                roles,
+                respond_to,
+            } => {
+                // chatters who already left are banned by nickname only
+                let target = match self.find_chatter(&nickname) {
+                    Some(_) => self.target(&nickname, &roles).map(Some),
+                    None => Ok(None),
+                };
+                let reply = match target {
+                    Err(reply) => reply,
+                    Ok(peer_id) => {
+                        let auth = peer_id.and_then(|peer_id| self.chatters[&peer_id].auth.clone());
+                        match self.bans.ban(&nickname, auth.as_ref(), &by) {
+                            Err(err) => {
+                                tracing::error!("failed to save bans: {err}");
+                                err.to_string()
+                            }
+                            Ok(()) => {
+                                if let Some(peer_id) = peer_id {
+                                    self.kick(peer_id).await;
+                                }
+                                self.locale
+                                    .format(Message::Banned, &[("nickname", &nickname)])
+                            }
+                        }
+                    }
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::Unban {
+                nickname,
+                respond_to,
+            } => {
+                let reply = match self.bans.unban(&nickname) {
+                    Ok(true) => self
+                        .locale
+                        .format(Message::Unbanned, &[("nickname", &nickname)]),
+                    Ok(false) => self
+                        .locale
+                        .format(Message::NotBanned, &[("nickname", &nickname)]),
+                    Err(err) => {
+                        tracing::error!("failed to save bans: {err}");
+                        err.to_string()
+                    }
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::GetBans { respond_to } => {
+                let bans = self
+                    .bans
+                    .bans()
+                    .iter()
+                    .map(|ban| match &ban.identity {
+                        Some(identity) => format!("{} ({identity})", ban.nickname),
+                        None => ban.nickname.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                let reply = match bans.is_empty() {
+                    true => self.locale.text(Message::NoBans),
+                    false => self
+                        .locale
+                        .format(Message::Bans, &[("bans", &bans.join(", "))]),
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::Mute {
+                nickname,
+                duration,
+                roles,
+                respond_to,
+            } => {
+                let reply = match self.target(&nickname, &roles) {
+                    Err(reply) => reply,
+                    // a duration of zero lifts the mute
+                    Ok(peer_id) if duration.is_zero() => {
+                        self.muted.remove(&peer_id);
+                        self.locale
+                            .format(Message::Unmuted, &[("nickname", &nickname)])
+                    }
+                    Ok(peer_id) => {
+                        self.muted.insert(peer_id, Instant::now() + duration);
+                        self.locale.format(
+                            Message::Muted,
+                            &[
+                                ("nickname", &nickname),
+                                ("minutes", &(duration.as_secs() / 60)),
+                            ],
+                        )
+                    }
+                };
+                respond_to.send(reply).unwrap();
+            }
+            BotMessage::IsMuted {
+                peer_id,
+                respond_to,
+            } => {
+                let now = Instant::now();
+                self.muted.retain(|_, until| *until > now);
+                respond_to.send(self.muted.contains_key(&peer_id)).unwrap();
+            }
+        }
+    }
+
+    fn find_chatter(&self, nickname: &str) -> Option<u64> {
+        self.chatters
+            .iter()
+            .find(|(_, chatter)| chatter.nickname.eq_ignore_ascii_case(nickname))
+            .map(|(&peer_id, _)| peer_id)
+    }
+
+    // peer id of the chatter called `nickname`, who must be in the room, not be the bot and
+    // rank below a caller with `roles`
+    fn target(&self, nickname: &str, roles: &[Role]) -> Result<u64, String> {
+        let peer_id = self.find_chatter(nickname).ok_or_else(|| {
+            self.locale
+                .format(Message::NoChatter, &[("nickname", &nickname)])
+        })?;
+        if peer_id == self.self_peer_id.load(Ordering::Relaxed) {
+            return Err(self.locale.text(Message::CannotModerateBot));
+        }
+        if !outranks(roles, &self.chatters[&peer_id].roles) {
+            return Err(self
+                .locale
+                .format(Message::Outranked, &[("nickname", &nickname)]));
+        }
+        Ok(peer_id)
+    }
+
+    async fn kick(&self, peer_id: u64) {
+        if let Some(socket) = self.room_socket.as_ref() {
+            let _ = socket.emit("kickUser", json!(peer_id)).await;
+        }
+    }
+
+    // the reason `rules` cannot be applied, if there is one
+    fn check_rules(&self, rules: &Rules) -> Result<(), String> {
+        let peer_id = self.self_peer_id.load(Ordering::Relaxed);
+        if self.game_socket.is_none() || self.leader_peer_id != Some(peer_id) {
+            return Err(self.locale.text(Message::NotLeader));
+        }
+        if self.milestone != "seating" {
+            return Err(self.locale.text(Message::RoundOngoing));
+        }
+        if let (Some(constants), Some(min), Some(max)) = (
+            &self.constants,
+            rules.min_bomb_duration,
+            rules.max_bomb_duration,
+        ) {
+            if min < constants.min_bomb_duration || max > constants.max_bomb_duration {
+                return Err(self.locale.format(
+                    Message::BombOutOfRange,
+                    &[
+                        ("min", &constants.min_bomb_duration),
+                        ("max", &constants.max_bomb_duration),
+                    ],
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // runs the page of `cursor` on the blocking pool, a heavy query must not hold up the turns
+    fn search(&self, cursor: Cursor, respond_to: oneshot::Sender<String>) {
+        let dictionary = Arc::clone(&self.dictionary);
+        let locale = self.locale;
+        tokio::task::spawn_blocking(move || {
+            let Cursor {
+                query,
+                pattern,
+                skipped,
+                page,
+                ..
+            } = cursor;
+            let result = query.run(&pattern, &dictionary, &skipped, SEARCH_BUDGET);
+            let reply = match result {
+                Ok(result) if result.is_empty() => {
+                    let pattern = query.pattern.as_ref().unwrap_or(&pattern);
+                    locale.format(Message::NoResult, &[("query", pattern)])
+                }
+                Ok(result) => {
+                    let pages = result.len().div_ceil(RESULTS_PER_PAGE);
+                    let words = result
+                        .iter()
+                        .enumerate()
+                        .skip((page - 1).saturating_mul(RESULTS_PER_PAGE))
+                        .take(RESULTS_PER_PAGE)
+                        // sorted results are ranked
+                        .map(|(index, &word)| match query.sort {
+                            Some(_) => format!("{}. {word}", index + 1),
+                            None => word.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if page > pages {
+                        locale.format(Message::NoPage, &[("page", &page), ("pages", &pages)])
+                    } else {
+                        locale.format(
+                            Message::Results,
+                            &[
+                                ("count", &result.len()),
+                                ("page", &page),
+                                ("pages", &pages),
+                                ("words", &words),
+                            ],
+                        )
+                    }
+                }
+                Err(SearchError::InvalidRegex(error)) => {
+                    locale.format(Message::InvalidRegex, &[("error", &error)])
+                }
+                Err(SearchError::TooExpensive) => locale.text(Message::ExpensiveRegex),
+                Err(SearchError::TimedOut) => locale.text(Message::SearchTimedOut),
+            };
+            let _ = respond_to.send(reply);
+        });
+    }
+}
+
+// word -> definition, none when the file is missing so that `!define` can say so
+fn load_definitions(path: &str) -> HashMap<String, String> {
+    let definitions = match std::fs::read_to_string(path) {
+        Ok(definitions) => definitions,
+        Err(err) => {
+            tracing::warn!("no definitions loaded from {path}: {err}");
+            return HashMap::new();
+        }
+    };
+    match serde_json::from_str::<HashMap<String, String>>(&definitions) {
+        Ok(definitions) => definitions
+            .into_iter()
+            .map(|(word, definition)| (word.to_lowercase(), definition))
+            .collect(),
+        Err(err) => {
+            tracing::error!("invalid definitions in {path}: {err}");
+            HashMap::new()
         }
     }
 }
@@ -303,25 +956,48 @@ async fn run_my_bot(mut bot: Bot) {
     }
 }
 
-#[allow(clippy::new_without_default)]
 #[derive(Clone)]
 pub struct BotHandle {
     sender: mpsc::Sender<BotMessage>,
 }
 
 impl BotHandle {
-    pub fn new() -> Self {
+    /// Starts the bot of `room_code`, failing if its stats or bans cannot be read.
+    pub fn new(
+        room_code: &str,
+        room: RoomConfig,
+        perks: PerksConfig,
+        chat: ChatQueue,
+    ) -> anyhow::Result<Self> {
         let (sender, receiver) = mpsc::channel::<BotMessage>(512);
-        let bot = Bot::new(receiver);
+        let dictionary = std::fs::read_to_string("src/dictionaries/english.json").unwrap();
+        let mut dictionary = serde_json::from_str::<Dictionary>(&dictionary).unwrap();
+        shuffle(&mut dictionary.dictionary);
+        dictionary.syllables = count_syllables(&dictionary.dictionary);
+        dictionary.definitions = load_definitions(DEFINITIONS_PATH);
+        let bot = Bot::new(
+            receiver,
+            room_code.to_string(),
+            room,
+            perks,
+            chat,
+            dictionary,
+        )?;
         tokio::spawn(run_my_bot(bot));
 
-        Self { sender }
+        Ok(Self { sender })
+    }
+
+    /// Number of messages waiting to be handled by the bot.
+    pub fn mailbox_depth(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
     }
 
-    pub async fn get_words(&self, query: String) -> String {
+    pub async fn get_words(&self, query: Query, user: String) -> String {
         let (send, recv) = oneshot::channel::<String>();
         let msg = BotMessage::GetWords {
             query,
+            user,
             respond_to: send,
         };
 
@@ -329,27 +1005,26 @@ impl BotHandle {
         recv.await.expect("Bot has been killed")
     }
 
-    pub async fn set_peer_id(&self, peer_id: u64) {
-        let msg = BotMessage::SetPeerId { peer_id };
-        self.sender.send(msg).await.unwrap();
-    }
-
-    pub async fn get_peer_id(&self) -> u64 {
-        let (send, recv) = oneshot::channel::<u64>();
-        let msg = BotMessage::GetPeerId { respond_to: send };
+    /// Next page of the last search of `user`.
+    pub async fn get_more_words(&self, user: String) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::GetMoreWords {
+            user,
+            respond_to: send,
+        };
 
         self.sender.send(msg).await.unwrap();
         recv.await.expect("Bot has been killed")
     }
 
-    pub async fn set_room_creator(&self, creator: String) {
-        let msg = BotMessage::SetRoomCreator { creator };
+    pub async fn set_peer_id(&self, peer_id: u64) {
+        let msg = BotMessage::SetPeerId { peer_id };
         self.sender.send(msg).await.unwrap();
     }
 
-    pub async fn get_room_creator(&self) -> String {
-        let (send, recv) = oneshot::channel::<String>();
-        let msg = BotMessage::GetRoomCreator { respond_to: send };
+    pub async fn get_peer_id(&self) -> u64 {
+        let (send, recv) = oneshot::channel::<u64>();
+        let msg = BotMessage::GetPeerId { respond_to: send };
 
         self.sender.send(msg).await.unwrap();
         recv.await.expect("Bot has been killed")
@@ -447,6 +1122,23 @@ impl BotHandle {
         recv.await.expect("Bot has been killed")
     }
 
+    pub async fn get_players(&self) -> Vec<(u64, PlayerStats)> {
+        let (send, recv) = oneshot::channel::<Vec<(u64, PlayerStats)>>();
+        let msg = BotMessage::GetPlayers { respond_to: send };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn find_player(&self, nickname: String) -> Option<PlayerStats> {
+        let (send, recv) = oneshot::channel::<Option<PlayerStats>>();
+        let msg = BotMessage::FindPlayer {
+            nickname,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
     pub async fn set_room_socket(&self, socket: Client) {
         let msg = BotMessage::SetRoomSocket { socket };
         self.sender.send(msg).await.unwrap();
@@ -457,6 +1149,12 @@ impl BotHandle {
         self.sender.send(msg).await.unwrap();
     }
 
+    /// Sends `message` when the chat is quiet, merged with other announcements if it is not.
+    pub async fn announce(&self, message: String) {
+        let msg = BotMessage::Announce { message };
+        self.sender.send(msg).await.unwrap();
+    }
+
     pub async fn increment_lives(&self, peer_id: u64) -> u64 {
         let (send, recv) = oneshot::channel::<u64>();
         let msg = BotMessage::UpdateLives {
@@ -466,4 +1164,300 @@ impl BotHandle {
         self.sender.send(msg).await.unwrap();
         recv.await.expect("Bot has been killed")
     }
+
+    pub async fn record_game(&self, winner: Option<String>) {
+        let msg = BotMessage::RecordGame { winner };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn get_leaders(&self, category: Category, window: Window) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::GetLeaders {
+            category,
+            window,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn get_rank(&self, nickname: String, category: Category, window: Window) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::GetRank {
+            nickname,
+            category,
+            window,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn set_discord(&self, bridge: DiscordBridge) {
+        let msg = BotMessage::SetDiscord { bridge };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn set_strategy(&self, strategy: WordStrategy) {
+        let msg = BotMessage::SetStrategy { strategy };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn get_strategy(&self) -> WordStrategy {
+        let (send, recv) = oneshot::channel::<WordStrategy>();
+        let msg = BotMessage::GetStrategy { respond_to: send };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn get_locale(&self) -> Locale {
+        let (send, recv) = oneshot::channel::<Locale>();
+        let msg = BotMessage::GetLocale { respond_to: send };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn set_prefix(&self, prefix: String) {
+        let msg = BotMessage::SetPrefix { prefix };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn get_prefix(&self) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::GetPrefix { respond_to: send };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn get_definition(&self, word: String) -> Option<String> {
+        let (send, recv) = oneshot::channel::<Option<String>>();
+        let msg = BotMessage::GetDefinition {
+            word,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn set_setup(&self, setup: Setup) {
+        let msg = BotMessage::SetSetup { setup };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn set_leader(&self, peer_id: u64) {
+        let msg = BotMessage::SetLeader { peer_id };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn set_milestone(&self, name: String, rules_locked: bool) {
+        let msg = BotMessage::SetMilestone { name, rules_locked };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    /// Applies `rules` to the room, returning the reply for the chat.
+    pub async fn set_rules(&self, rules: Rules) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::SetRules {
+            rules,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    /// Registers a chatter who joined, returning whether they were kicked for being banned.
+    pub async fn add_chatter(
+        &self,
+        peer_id: u64,
+        nickname: String,
+        auth: Option<Auth>,
+        roles: Vec<Role>,
+    ) -> bool {
+        let (send, recv) = oneshot::channel::<bool>();
+        let msg = BotMessage::AddChatter {
+            peer_id,
+            nickname,
+            auth,
+            roles,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    /// Keeps the roles of a chatter up to date, they can be made moderator after joining.
+    pub async fn set_chatter_roles(&self, peer_id: u64, roles: Vec<Role>) {
+        let msg = BotMessage::SetChatterRoles { peer_id, roles };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    pub async fn remove_chatter(&self, peer_id: u64) {
+        let msg = BotMessage::RemoveChatter { peer_id };
+        self.sender.send(msg).await.unwrap();
+    }
+
+    /// Kicks `nickname` for a caller with `roles`, who must outrank them.
+    pub async fn kick(&self, nickname: String, roles: Vec<Role>) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::Kick {
+            nickname,
+            roles,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    /// Bans `nickname` on behalf of `by`, kicking them if they are in the room.
+    pub async fn ban(&self, nickname: String, by: String, roles: Vec<Role>) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::Ban {
+            nickname,
+            by,
+            roles,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn unban(&self, nickname: String) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::Unban {
+            nickname,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    /// Banned chatters, with the account they are banned by.
+    pub async fn get_bans(&self) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::GetBans { respond_to: send };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    /// Ignores the commands of `nickname` for `duration`, zero lifting the mute.
+    pub async fn mute(&self, nickname: String, duration: Duration, roles: Vec<Role>) -> String {
+        let (send, recv) = oneshot::channel::<String>();
+        let msg = BotMessage::Mute {
+            nickname,
+            duration,
+            roles,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+
+    pub async fn is_muted(&self, peer_id: u64) -> bool {
+        let (send, recv) = oneshot::channel::<bool>();
+        let msg = BotMessage::IsMuted {
+            peer_id,
+            respond_to: send,
+        };
+        self.sender.send(msg).await.unwrap();
+        recv.await.expect("Bot has been killed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChatConfig;
+
+    fn bot(words: &[&str]) -> Bot {
+        let words = words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>();
+        let dictionary = Dictionary {
+            syllables: count_syllables(&words),
+            dictionary: words,
+            sn: Vec::new(),
+            definitions: HashMap::new(),
+        };
+        let (_, receiver) = mpsc::channel(1);
+        Bot::new(
+            receiver,
+            "ABCD".to_string(),
+            RoomConfig::default(),
+            PerksConfig::default(),
+            crate::chat::start(ChatConfig::default()),
+            dictionary,
+        )
+        .unwrap()
+    }
+
+    async fn search(bot: &mut Bot, query: &str) -> String {
+        let (respond_to, reply) = oneshot::channel();
+        bot.handle_message(BotMessage::GetWords {
+            query: query.parse().unwrap(),
+            user: "mika".to_string(),
+            respond_to,
+        })
+        .await;
+        reply.await.unwrap()
+    }
+
+    // `content` in a file of its own, named after the test
+    fn fixture(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("kaoruko-{name}-{}.json", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn loads_definitions() {
+        let path = fixture("definitions", r#"{"Bomb": "an explosive device"}"#);
+        let definitions = load_definitions(&path);
+        assert_eq!(definitions["bomb"], "an explosive device");
+        assert_eq!(definitions.len(), 1);
+        // the bot keeps running without them
+        assert!(load_definitions(&fixture("invalid-definitions", "{\"bomb\":")).is_empty());
+        assert!(load_definitions("missing-definitions.json").is_empty());
+    }
+
+    #[tokio::test]
+    async fn looks_definitions_up_whatever_their_case() {
+        let mut bot = bot(&["bomb"]);
+        Arc::get_mut(&mut bot.dictionary)
+            .unwrap()
+            .definitions
+            .insert("bomb".to_string(), "an explosive device".to_string());
+        let (respond_to, definition) = oneshot::channel();
+        bot.handle_message(BotMessage::GetDefinition {
+            word: "BOMB".to_string(),
+            respond_to,
+        })
+        .await;
+        assert_eq!(
+            definition.await.unwrap().as_deref(),
+            Some("an explosive device")
+        );
+    }
+
+    #[tokio::test]
+    async fn forgets_used_words_between_rounds() {
+        let mut bot = bot(&["abc", "abd"]);
+        bot.handle_message(BotMessage::AddWord {
+            word: "abc".to_string(),
+        })
+        .await;
+        assert_eq!(
+            search(&mut bot, "ab unused").await,
+            "results(1) — page 1/1: abd"
+        );
+        bot.handle_message(BotMessage::SetMilestone {
+            name: "seating".to_string(),
+            rules_locked: false,
+        })
+        .await;
+        assert_eq!(
+            search(&mut bot, "ab unused").await,
+            "results(2) — page 1/1: abc, abd"
+        );
+    }
 }
@@ -0,0 +1,239 @@
+use rust_socketio::asynchronous::Client;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::config::ChatConfig;
+use crate::utils::{split, MAX_CHAT_LENGTH};
+
+// announcements waiting at most, older ones are dropped first
+const MAX_PENDING_ANNOUNCEMENTS: usize = 10;
+// between merged announcements
+const SEPARATOR: &str = " — ";
+
+/// How urgently a message should reach the room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    // replies to commands and operators, sent in order
+    High,
+    // perks, greetings and the like, merged together when the chat is busy
+    Low,
+}
+
+enum Outgoing {
+    Message { text: String, priority: Priority },
+    Socket(Client),
+}
+
+/// Sending half of the chat queue, messages reach the room at the pace jklm allows.
+#[derive(Debug, Clone)]
+pub struct ChatQueue {
+    outbox: mpsc::Sender<Outgoing>,
+}
+
+impl ChatQueue {
+    pub fn say(&self, text: String) {
+        self.send(Outgoing::Message {
+            text,
+            priority: Priority::High,
+        });
+    }
+
+    pub fn announce(&self, text: String) {
+        self.send(Outgoing::Message {
+            text,
+            priority: Priority::Low,
+        });
+    }
+
+    /// Room socket to send the chat to, messages sent before it are dropped.
+    pub fn set_socket(&self, socket: Client) {
+        self.send(Outgoing::Socket(socket));
+    }
+
+    fn send(&self, outgoing: Outgoing) {
+        if let Err(err) = self.outbox.try_send(outgoing) {
+            tracing::warn!("dropped chat message: {err}");
+        }
+    }
+}
+
+/// Starts sending queued messages to the room.
+pub fn start(config: ChatConfig) -> ChatQueue {
+    let (outbox, mut receiver) = mpsc::channel::<Outgoing>(512);
+    let mut throttle = Throttle::new(config.burst, Duration::from_millis(config.interval_ms));
+    tokio::spawn(async move {
+        let mut pending = Pending::default();
+        let mut socket = None;
+        loop {
+            let wait = (!pending.is_empty()).then(|| throttle.wait(Instant::now()));
+            tokio::select! {
+                outgoing = receiver.recv() => match outgoing {
+                    Some(Outgoing::Message { text, priority }) => pending.push(&text, priority),
+                    Some(Outgoing::Socket(client)) => socket = Some(client),
+                    None => break,
+                },
+                _ = tokio::time::sleep(wait.unwrap_or_default()), if wait.is_some() => {
+                    let Some(message) = pending.next() else {
+                        continue;
+                    };
+                    throttle.take(Instant::now());
+                    // the api and the console may talk before the room is joined
+                    if let Some(socket) = socket.as_ref() {
+                        let _ = socket.emit("chat", message).await;
+                    }
+                }
+            }
+        }
+    });
+    ChatQueue { outbox }
+}
+
+/// Messages waiting for their turn, already split to fit the chat.
+#[derive(Debug, Default)]
+struct Pending {
+    high: VecDeque<String>,
+    low: VecDeque<String>,
+}
+
+impl Pending {
+    fn push(&mut self, text: &str, priority: Priority) {
+        let parts = split(text, MAX_CHAT_LENGTH);
+        match priority {
+            Priority::High => self.high.extend(parts),
+            Priority::Low => {
+                self.low.extend(parts);
+                while self.low.len() > MAX_PENDING_ANNOUNCEMENTS {
+                    self.low.pop_front();
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.high.is_empty() && self.low.is_empty()
+    }
+
+    // the next message to send, waiting announcements being merged into one
+    fn next(&mut self) -> Option<String> {
+        if let Some(message) = self.high.pop_front() {
+            return Some(message);
+        }
+        let mut message = self.low.pop_front()?;
+        while let Some(announcement) = self.low.front() {
+            let length =
+                message.chars().count() + SEPARATOR.chars().count() + announcement.chars().count();
+            if length > MAX_CHAT_LENGTH {
+                break;
+            }
+            message = format!("{message}{SEPARATOR}{announcement}");
+            self.low.pop_front();
+        }
+        Some(message)
+    }
+}
+
+/// Lets `burst` messages through at once, then one every `interval`.
+#[derive(Debug)]
+struct Throttle {
+    burst: u32,
+    interval: Duration,
+    tokens: u32,
+    // when the last token was gained
+    refilled: Instant,
+}
+
+impl Throttle {
+    fn new(burst: u32, interval: Duration) -> Self {
+        Self {
+            burst: burst.max(1),
+            interval,
+            tokens: burst.max(1),
+            refilled: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if self.tokens == self.burst || self.interval.is_zero() {
+            self.tokens = self.burst;
+            self.refilled = now;
+            return;
+        }
+        let gained = (now.duration_since(self.refilled).as_millis() / self.interval.as_millis())
+            .min(u128::from(self.burst)) as u32;
+        if gained > 0 {
+            self.tokens = (self.tokens + gained).min(self.burst);
+            self.refilled = match self.tokens == self.burst {
+                true => now,
+                false => self.refilled + self.interval * gained,
+            };
+        }
+    }
+
+    /// Time until a message may be sent.
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        match self.tokens {
+            0 => (self.refilled + self.interval).saturating_duration_since(now),
+            _ => Duration::ZERO,
+        }
+    }
+
+    fn take(&mut self, now: Instant) {
+        self.refill(now);
+        self.tokens = self.tokens.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_replies_before_announcements() {
+        let mut pending = Pending::default();
+        pending.push("nice long word", Priority::Low);
+        pending.push("results(2): ab, abc", Priority::High);
+        pending.push("a hyphen", Priority::Low);
+        assert_eq!(pending.next().as_deref(), Some("results(2): ab, abc"));
+        // announcements are merged while they fit
+        assert_eq!(pending.next().as_deref(), Some("nice long word — a hyphen"));
+        assert_eq!(pending.next(), None);
+    }
+
+    #[test]
+    fn splits_long_messages() {
+        let mut pending = Pending::default();
+        let words = vec!["word"; 100].join(", ");
+        pending.push(&words, Priority::High);
+        assert_eq!(pending.high.len(), 2);
+        assert!(pending
+            .high
+            .iter()
+            .all(|part| part.chars().count() <= MAX_CHAT_LENGTH));
+    }
+
+    #[test]
+    fn drops_the_oldest_announcements() {
+        let mut pending = Pending::default();
+        for i in 0..=MAX_PENDING_ANNOUNCEMENTS {
+            pending.push(&i.to_string(), Priority::Low);
+        }
+        assert_eq!(pending.low.front().map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn throttles_after_a_burst() {
+        let interval = Duration::from_secs(1);
+        let mut throttle = Throttle::new(2, interval);
+        let start = Instant::now();
+        throttle.take(start);
+        throttle.take(start);
+        assert_eq!(throttle.wait(start), interval);
+        let later = start + Duration::from_millis(400);
+        assert_eq!(throttle.wait(later), Duration::from_millis(600));
+        assert_eq!(throttle.wait(start + interval), Duration::ZERO);
+        throttle.take(start + interval);
+        assert_eq!(throttle.wait(start + interval), interval);
+    }
+}
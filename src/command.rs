@@ -1,29 +1,572 @@
+use anyhow::anyhow;
 use kaoruko_derive::CommandParser;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::bot::BotHandle;
+use crate::i18n::{Locale, Message};
+use crate::moderation::MuteTarget;
+use crate::query::Query;
+use crate::roles::Role;
+use crate::rules::Rules;
+use crate::stats::{Category, Window};
+use crate::utils::{shorten, MAX_CHAT_LENGTH};
 
 #[derive(CommandParser)]
+#[config(reference_json)]
 pub enum Command {
     #[config(
         alias = "c",
         roles = ["anyone"],
-        description = "returns solves for a given query string",
+        description(
+            en = "returns solves for a regex, or for the current syllable, filtered with len:5-10, has:xy, no:z, hyphen:yes, space:no or unused, ranked with sort:longest, shortest or rarest and paged with page 2",
+            fr = "donne des solutions pour une regex, ou pour la syllabe en cours, filtrées avec len:5-10, has:xy, no:z, hyphen:yes, space:no ou unused, classées avec sort:longest, shortest ou rarest et paginées avec page 2",
+        ),
+        cooldown = "5s",
+        global_cooldown = "1s",
+        cooldown_exempt = ["developer", "moderator"],
         string_options(
-            (required, allow_whitespaces)
+            (allow_whitespaces)
         ),
+        handler = search,
     )]
-    Search,
+    Search { query: Option<Query> },
     #[config(
-        description = "kicks the bot out of the room",
+        alias = "m",
+        roles = ["anyone"],
+        description(
+            en = "show the next page of your last search",
+            fr = "affiche la page suivante de ta dernière recherche",
+        ),
+        cooldown = "5s",
+        global_cooldown = "1s",
+        cooldown_exempt = ["developer", "moderator"],
+        handler = more,
+    )]
+    More,
+    #[config(
+        description(
+            en = "kicks the bot out of the room",
+            fr = "fait quitter le salon au bot",
+        ),
         roles = ["developer", "creator"],
+        handler = exit,
     )]
     Exit,
     #[config(
         alias = "sn",
         roles = ["developer"],
-        description = "start the game now",
+        description(en = "start the game now", fr = "lance la partie maintenant"),
+        handler = start_now,
     )]
     StartNow,
-    #[config(alias = "h", description = "get help for a command", roles = ["anyone"])]
-    Help,
-    #[config(alias = "s", description = "display player stats", roles = ["anyone"])]
-    Stats,
+    #[config(
+        alias = "h",
+        description(en = "get help for a command", fr = "affiche l'aide d'une commande"),
+        roles = ["anyone"],
+        string_options(()),
+        handler = help,
+    )]
+    Help { command: Option<String> },
+    #[config(
+        alias = "s",
+        description(
+            en = "display your stats or the stats of a given player",
+            fr = "affiche tes stats ou celles d'un joueur",
+        ),
+        roles = ["anyone"],
+        string_options((allow_whitespaces)),
+        handler = stats,
+    )]
+    Stats { player: Option<String> },
+    #[config(
+        alias = "t",
+        roles = ["anyone"],
+        description(
+            en = "show leaders for a stat (words, subs, longs, hyphens, multi, lives), optionally for today or this week",
+            fr = "affiche les meilleurs pour une stat (words, subs, longs, hyphens, multi, lives), pour aujourd'hui ou cette semaine au besoin",
+        ),
+        string_options((required), ()),
+        handler = top,
+    )]
+    Top {
+        stat: Category,
+        window: Option<Window>,
+    },
+    #[config(
+        alias = "r",
+        roles = ["anyone"],
+        description(
+            en = "show your rank for a stat, optionally for today or this week",
+            fr = "affiche ton rang pour une stat, pour aujourd'hui ou cette semaine au besoin",
+        ),
+        string_options((), ()),
+        handler = rank,
+    )]
+    Rank {
+        stat: Option<Category>,
+        window: Option<Window>,
+    },
+    #[config(
+        alias = "d",
+        roles = ["anyone"],
+        description(
+            en = "show what a word means",
+            fr = "affiche le sens d'un mot",
+        ),
+        cooldown = "3s",
+        string_options((required, allow_whitespaces)),
+        handler = define,
+    )]
+    Define { word: String },
+    #[config(
+        roles = ["creator", "moderator", "trusted"],
+        description(
+            en = "change the rules while the bot leads the room: lives 2-3, bomb 5-20, difficulty beginner, medium or hard, dictionary fr, alphabet abc, or preset practice or competitive",
+            fr = "change les règles quand le bot mène le salon : lives 2-3, bomb 5-20, difficulty beginner, medium ou hard, dictionary fr, alphabet abc, ou preset practice ou competitive",
+        ),
+        string_options((required, allow_whitespaces)),
+        handler = rules,
+    )]
+    Rules { rules: Rules },
+    #[config(
+        roles = ["creator"],
+        description(
+            en = "show the command prefix, or change it for this room",
+            fr = "affiche le préfixe des commandes, ou le change pour ce salon",
+        ),
+        string_options(()),
+        handler = prefix,
+    )]
+    Prefix { prefix: Option<String> },
+    #[config(
+        roles = ["creator", "moderator"],
+        description(
+            en = "kick a chatter out of the room",
+            fr = "expulse quelqu'un du salon",
+        ),
+        string_options((required, allow_whitespaces)),
+        handler = kick,
+    )]
+    Kick { nickname: String },
+    #[config(
+        roles = ["creator", "moderator"],
+        description(
+            en = "kick a chatter and keep them out, by their account and nickname, even after a restart",
+            fr = "expulse quelqu'un et l'empêche de revenir, par son compte et son pseudo, même après un redémarrage",
+        ),
+        string_options((required, allow_whitespaces)),
+        handler = ban,
+    )]
+    Ban { nickname: String },
+    #[config(
+        roles = ["creator", "moderator"],
+        description(
+            en = "let a banned chatter back in, by nickname or by account as shown by !bans",
+            fr = "laisse revenir quelqu'un de banni, par son pseudo ou son compte comme affiché par !bans",
+        ),
+        string_options((required, allow_whitespaces)),
+        handler = unban,
+    )]
+    Unban { nickname: String },
+    #[config(
+        roles = ["creator", "moderator"],
+        description(
+            en = "list the banned chatters and their accounts",
+            fr = "liste les personnes bannies et leurs comptes",
+        ),
+        handler = bans,
+    )]
+    Bans,
+    #[config(
+        roles = ["creator", "moderator"],
+        description(
+            en = "ignore the commands of a chatter for some minutes, as in !mute nickname 30, 10 by default, 0 to lift it",
+            fr = "ignore les commandes de quelqu'un pendant quelques minutes, comme !mute pseudo 30, 10 par défaut, 0 pour lever",
+        ),
+        string_options((required, allow_whitespaces)),
+        handler = mute,
+    )]
+    Mute { target: MuteTarget },
+}
+
+/// Whoever is running a command, from the room or from outside of it.
+pub struct Caller {
+    pub nickname: String,
+    // only set when the caller is inside the room
+    pub peer_id: Option<u64>,
+    pub roles: Vec<Role>,
+}
+
+impl Caller {
+    /// Caller from outside of the room, trusted with every command.
+    pub fn operator(nickname: impl Into<String>) -> Self {
+        Self {
+            nickname: nickname.into(),
+            peer_id: None,
+            roles: vec![Role::Developer],
+        }
+    }
+
+    /// Peer id inside the room, nickname outside of it.
+    pub fn id(&self) -> String {
+        self.peer_id
+            .map_or_else(|| self.nickname.clone(), |peer_id| peer_id.to_string())
+    }
+}
+
+/// Shared by the handlers of every command.
+pub struct Context {
+    pub caller: Caller,
+    pub bot: Arc<BotHandle>,
+    // halts the bot
+    pub notifier: Arc<Notify>,
+}
+
+// longest prefix, it is typed before every command
+const MAX_PREFIX_LENGTH: usize = 3;
+
+/// Refuses prefixes that would turn ordinary messages into commands.
+pub fn check_prefix(prefix: &str) -> anyhow::Result<()> {
+    if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH {
+        return Err(anyhow!(
+            "prefix must be 1 to {MAX_PREFIX_LENGTH} characters long"
+        ));
+    }
+    if prefix
+        .chars()
+        .any(|c| c.is_alphanumeric() || c.is_whitespace())
+    {
+        return Err(anyhow!("prefix cannot contain letters, digits or spaces"));
+    }
+    Ok(())
+}
+
+/// The command of a message addressing the bot as `name`, such as `kaoruko, search ab` or
+/// `@kaoruko !search ab`.
+pub fn mentioned<'a>(message: &'a str, name: &str, prefix: &str) -> Option<&'a str> {
+    let message = message.trim_start();
+    let message = message.strip_prefix('@').unwrap_or(message);
+    let end = message
+        .find(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        .unwrap_or(message.len());
+    let (addressee, command) = message.split_at(end);
+    if !addressee.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let command = command.trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == ':');
+    let command = command.strip_prefix(prefix).unwrap_or(command);
+    (!command.is_empty()).then_some(command)
+}
+
+/// The command reference as `markdown` or `json`, printed by `kaoruko commands`.
+pub fn reference(format: &str, locale: Locale) -> anyhow::Result<String> {
+    match format {
+        "markdown" | "md" => Ok(Command::reference_markdown(locale.code())),
+        "json" => Ok(Command::reference_json()),
+        _ => Err(anyhow!(
+            "unknown format `{format}`, expected one of: markdown, json"
+        )),
+    }
+}
+
+async fn search(ctx: &Context, query: Option<Query>) -> Option<String> {
+    let query = query.unwrap_or_default();
+    Some(ctx.bot.get_words(query, ctx.caller.id()).await)
+}
+
+async fn more(ctx: &Context) -> Option<String> {
+    Some(ctx.bot.get_more_words(ctx.caller.id()).await)
+}
+
+async fn exit(ctx: &Context) -> Option<String> {
+    let notifier = Arc::clone(&ctx.notifier);
+    // give the reply some time to reach the room
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        notifier.notify_one();
+    });
+    Some(ctx.bot.get_locale().await.text(Message::Sayonara))
+}
+
+async fn start_now(ctx: &Context) -> Option<String> {
+    ctx.bot.start_round_now().await;
+    None
+}
+
+async fn help(ctx: &Context, command: Option<String>) -> Option<String> {
+    let locale = ctx.bot.get_locale().await;
+    let reply = match command {
+        Some(command) => match Command::find(&command) {
+            Some(info) => describe(info, locale),
+            None => describe_error(&Command::unknown(&command), locale),
+        },
+        None => {
+            let commands = Command::COMMANDS
+                .iter()
+                .filter(|info| info.is_permitted(&ctx.caller.roles))
+                .map(|info| match info.aliases {
+                    [] => info.name.to_string(),
+                    aliases => format!("{} ({})", info.name, aliases.join(", ")),
+                })
+                .collect::<Vec<_>>();
+            locale.format(Message::CommandList, &[("commands", &commands.join(", "))])
+        }
+    };
+    Some(reply)
+}
+
+// description, usage, aliases and roles of `info`, as `!help <command>` shows them
+fn describe(info: &CommandInfo, locale: Locale) -> String {
+    let mut parts = vec![locale.format(
+        Message::Help,
+        &[
+            ("description", &info.description_in(locale.code())),
+            ("usage", &info.usage),
+        ],
+    )];
+    if !info.aliases.is_empty() {
+        let aliases = info.aliases.join(", ");
+        parts.push(locale.format(Message::HelpAliases, &[("aliases", &aliases)]));
+    }
+    if !info.roles.is_empty() {
+        let roles = info
+            .roles
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        parts.push(locale.format(Message::HelpRoles, &[("roles", &roles)]));
+    }
+    parts.join(" — ")
+}
+
+/// `err` of parsing a command, worded in `locale` when the parser gave its reason.
+pub fn describe_error(err: &anyhow::Error, locale: Locale) -> String {
+    if let Some(UnknownCommand { suggestions, .. }) = err.downcast_ref::<UnknownCommand>() {
+        if suggestions.is_empty() {
+            return locale.text(Message::UnknownCommand);
+        }
+        let commands = suggestions
+            .iter()
+            .map(|name| format!("!{name}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return locale.format(Message::DidYouMean, &[("commands", &commands)]);
+    }
+    let Some(err) = err.downcast_ref::<CommandError>() else {
+        return err.to_string();
+    };
+    match err {
+        CommandError::NotEligible => locale.text(Message::NotEligible),
+        CommandError::CoolingDown { command, seconds } => locale.format(
+            Message::CoolingDown,
+            &[("command", command), ("seconds", seconds)],
+        ),
+        CommandError::MissingArgument { argument, usage } => locale.format(
+            Message::MissingArgument,
+            &[("argument", argument), ("usage", usage)],
+        ),
+        CommandError::InvalidArgument {
+            argument,
+            error,
+            usage,
+        } => locale.format(
+            Message::InvalidArgument,
+            &[("argument", argument), ("error", error), ("usage", usage)],
+        ),
+        CommandError::TooManyArguments { usage } => {
+            locale.format(Message::TooManyArguments, &[("usage", usage)])
+        }
+    }
+}
+
+// do not allow usage of this command on event seating
+async fn stats(ctx: &Context, player: Option<String>) -> Option<String> {
+    let Context { caller, bot, .. } = ctx;
+    let stats = match (&player, caller.peer_id) {
+        (Some(nickname), _) => bot.find_player(nickname.clone()).await,
+        (None, Some(peer_id)) => bot.get_player(peer_id).await,
+        (None, None) => bot.find_player(caller.nickname.clone()).await,
+    };
+    let locale = bot.get_locale().await;
+    let reply = if let Some(stats) = stats {
+        locale.format(
+            Message::Stats,
+            &[
+                ("nickname", &stats.nickname),
+                ("stats", &locale.stat_line(&stats)),
+            ],
+        )
+    } else {
+        locale.format(
+            Message::NoStats,
+            &[("nickname", player.as_ref().unwrap_or(&caller.nickname))],
+        )
+    };
+    Some(reply)
+}
+
+async fn top(ctx: &Context, stat: Category, window: Option<Window>) -> Option<String> {
+    Some(
+        ctx.bot
+            .get_leaders(stat, window.unwrap_or(Window::AllTime))
+            .await,
+    )
+}
+
+async fn rank(ctx: &Context, stat: Option<Category>, window: Option<Window>) -> Option<String> {
+    Some(
+        ctx.bot
+            .get_rank(
+                ctx.caller.nickname.clone(),
+                stat.unwrap_or(Category::Words),
+                window.unwrap_or(Window::AllTime),
+            )
+            .await,
+    )
+}
+
+async fn define(ctx: &Context, word: String) -> Option<String> {
+    let locale = ctx.bot.get_locale().await;
+    let definition = ctx.bot.get_definition(word.clone()).await;
+    Some(definition_reply(&word, definition.as_deref(), locale))
+}
+
+// a definition fits a single chat message, long ones are cut
+fn definition_reply(word: &str, definition: Option<&str>, locale: Locale) -> String {
+    let reply = match definition {
+        Some(definition) => locale.format(
+            Message::Definition,
+            &[("word", &word), ("definition", &definition)],
+        ),
+        None => locale.format(Message::NoDefinition, &[("word", &word)]),
+    };
+    shorten(&reply, MAX_CHAT_LENGTH)
+}
+
+async fn rules(ctx: &Context, rules: Rules) -> Option<String> {
+    Some(ctx.bot.set_rules(rules).await)
+}
+
+async fn prefix(ctx: &Context, prefix: Option<String>) -> Option<String> {
+    let locale = ctx.bot.get_locale().await;
+    let Some(prefix) = prefix else {
+        let prefix = ctx.bot.get_prefix().await;
+        return Some(locale.format(Message::Prefix, &[("prefix", &prefix)]));
+    };
+    if let Err(err) = check_prefix(&prefix) {
+        return Some(err.to_string());
+    }
+    let reply = locale.format(Message::PrefixSet, &[("prefix", &prefix)]);
+    ctx.bot.set_prefix(prefix).await;
+    Some(reply)
+}
+
+async fn kick(ctx: &Context, nickname: String) -> Option<String> {
+    Some(ctx.bot.kick(nickname, ctx.caller.roles.clone()).await)
+}
+
+async fn ban(ctx: &Context, nickname: String) -> Option<String> {
+    let Caller {
+        nickname: by,
+        roles,
+        ..
+    } = &ctx.caller;
+    Some(ctx.bot.ban(nickname, by.clone(), roles.clone()).await)
+}
+
+async fn unban(ctx: &Context, nickname: String) -> Option<String> {
+    Some(ctx.bot.unban(nickname).await)
+}
+
+async fn bans(ctx: &Context) -> Option<String> {
+    Some(ctx.bot.get_bans().await)
+}
+
+async fn mute(ctx: &Context, target: MuteTarget) -> Option<String> {
+    let duration = target.duration();
+    Some(
+        ctx.bot
+            .mute(target.nickname, duration, ctx.caller.roles.clone())
+            .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_prefixes_of_words() {
+        assert!(check_prefix("?").is_ok());
+        assert!(check_prefix(">>").is_ok());
+        assert!(check_prefix("").is_err());
+        assert!(check_prefix("k").is_err());
+        assert!(check_prefix("! ").is_err());
+        assert!(check_prefix("!!!!").is_err());
+    }
+
+    #[test]
+    fn words_errors_in_the_room_language() {
+        let err = |s: &str| describe_error(&s.parse::<Command>().err().unwrap(), Locale::Fr);
+        assert_eq!(
+            err("define"),
+            "argument <word> manquant — usage : !define <word...>"
+        );
+        assert_eq!(err("exit now"), "trop d'arguments — usage : !exit");
+        assert_eq!(
+            err("serch"),
+            "commande inconnue — voulais-tu dire !search ?"
+        );
+        assert_eq!(err("xyzzy"), "commande inconnue");
+        let err = "exit".parse_command(&[], "1").err().unwrap();
+        assert_eq!(
+            describe_error(&err, Locale::Fr),
+            "tu n'as pas le droit d'utiliser cette commande"
+        );
+    }
+
+    #[test]
+    fn describes_commands_in_the_room_language() {
+        let help = describe(Command::find("ban").unwrap(), Locale::Fr);
+        assert!(help.starts_with("expulse quelqu'un"));
+        assert!(help.ends_with("— usage : !ban <nickname...> — rôles : creator, moderator"));
+    }
+
+    #[test]
+    fn fits_definitions_in_one_message() {
+        assert_eq!(
+            definition_reply("bomb", Some("an explosive device"), Locale::En),
+            "bomb: an explosive device"
+        );
+        assert_eq!(
+            definition_reply("bomb", None, Locale::Fr),
+            "aucune définition trouvée pour bomb"
+        );
+        let definition = vec!["boom"; 100].join(" ");
+        let reply = definition_reply("bomb", Some(&definition), Locale::En);
+        assert!(reply.chars().count() <= MAX_CHAT_LENGTH);
+        assert!(reply.ends_with("boom…"));
+    }
+
+    #[test]
+    fn finds_commands_in_mentions() {
+        assert_eq!(
+            mentioned("kaoruko, search ab", "kaoruko", "!"),
+            Some("search ab")
+        );
+        assert_eq!(
+            mentioned("@Kaoruko !search ab", "kaoruko", "!"),
+            Some("search ab")
+        );
+        assert_eq!(
+            mentioned("kaoruko: top words", "kaoruko", "!"),
+            Some("top words")
+        );
+        assert_eq!(mentioned("kaoruko", "kaoruko", "!"), None);
+        assert_eq!(mentioned("kaorukoo, search", "kaoruko", "!"), None);
+        assert_eq!(mentioned("hey kaoruko", "kaoruko", "!"), None);
+    }
 }
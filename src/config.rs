@@ -0,0 +1,165 @@
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use crate::command::check_prefix;
+use crate::i18n::Locale;
+use crate::perks::PerksConfig;
+use crate::roles::RoleTable;
+
+// runtime configuration, read from the working directory
+pub const CONFIG_PATH: &str = "kaoruko.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub metrics: MetricsConfig,
+    pub discord: DiscordConfig,
+    pub console: ConsoleConfig,
+    pub api: ApiConfig,
+    pub roles: RoleTable,
+    pub commands: CommandsConfig,
+    pub room: RoomConfig,
+    pub chat: ChatConfig,
+    pub perks: PerksConfig,
+}
+
+impl Config {
+    /// Loads the configuration from `path`, using the defaults if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                let config = toml::from_str::<Self>(&content)?;
+                check_prefix(&config.room.prefix)?;
+                config.perks.check()?;
+                if config.api.enabled && config.api.token.is_empty() {
+                    return Err(anyhow::anyhow!("the api needs a `token` to be enabled"));
+                }
+                Ok(config)
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub address: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            address: SocketAddr::from(([127, 0, 0, 1], 9898)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    // must be a loopback address
+    pub address: SocketAddr,
+    // sent by clients as `Authorization: Bearer <token>`, required to enable the api
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: SocketAddr::from(([127, 0, 0, 1], 9899)),
+            token: String::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommandsConfig {
+    // ignore `!` words that are not commands, for rooms using `!` casually
+    pub silent_unknown: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RoomConfig {
+    // `en` or `fr`, for the room and the messages of the bot
+    pub language: Locale,
+    // marks chat messages as commands, the creator can change it with `!prefix`
+    pub prefix: String,
+    // announce perks in this room, they are counted either way
+    pub announce_perks: bool,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            language: Locale::default(),
+            prefix: "!".to_string(),
+            announce_perks: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    // messages sent at once before the bot slows down
+    pub burst: u32,
+    // then one message every interval
+    pub interval_ms: u64,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            burst: 3,
+            interval_ms: 1200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ConsoleConfig {
+    // read operator commands from stdin
+    pub enabled: bool,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub api_url: String,
+    // bot token, without the `Bot ` prefix
+    pub token: String,
+    pub channel_id: String,
+    pub poll_interval_ms: u64,
+    // discord user ids allowed to run commands from the channel, their roles come from `[roles]`
+    pub authorised_users: Vec<String>,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_url: "https://discord.com/api/v10".to_string(),
+            token: String::default(),
+            channel_id: String::default(),
+            poll_interval_ms: 2000,
+            authorised_users: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use rust_socketio::Payload;
+use std::sync::{Arc, LazyLock};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+
+use crate::bot::BotHandle;
+use crate::command::{Caller, Command, Context};
+use crate::metrics::METRICS;
+use crate::types::PlayerStats;
+
+// socket events, as printed by `tail`
+static EVENTS: LazyLock<broadcast::Sender<String>> = LazyLock::new(|| broadcast::channel(256).0);
+
+const USAGE: &str = "\
+commands:
+  say <message>       send a chat message as the bot
+  run <command> ...   run a chat command with developer rights
+  players             list players and their stats
+  start               start the round now
+  tail                toggle printing of socket events
+  help                show this message";
+
+/// Makes a socket event visible to `tail`, does nothing when nobody is tailing.
+pub fn publish(socket: &str, event: &str, payload: &Payload) {
+    if EVENTS.receiver_count() == 0 {
+        return;
+    }
+    let payload = match payload {
+        Payload::Text(values) => serde_json::to_string(values).unwrap_or_default(),
+        _ => "<binary>".to_string(),
+    };
+    let _ = EVENTS.send(format!("[{socket}] {event} {payload}"));
+}
+
+/// Reads operator commands from stdin until it is closed.
+pub async fn run(bot: Arc<BotHandle>, notifier: Arc<Notify>) -> anyhow::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut tail: Option<JoinHandle<()>> = None;
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        let (cmd, args) = line.split_once(' ').unwrap_or((line, ""));
+        match cmd {
+            "" => {}
+            "say" => bot.set_chat(args.to_string()).await,
+            "run" => match args.parse::<Command>() {
+                Ok(cmd) => {
+                    METRICS.commands.inc(cmd.name());
+                    let ctx = Context {
+                        caller: Caller::operator("console"),
+                        bot: Arc::clone(&bot),
+                        notifier: Arc::clone(&notifier),
+                    };
+                    let reply = cmd.dispatch(&ctx).await;
+                    println!("{}", reply.as_deref().unwrap_or("done"));
+                }
+                Err(err) => println!("{err}"),
+            },
+            "players" => {
+                let players = bot.get_players().await;
+                if players.is_empty() {
+                    println!("no players");
+                }
+                for (peer_id, player) in players {
+                    let PlayerStats {
+                        nickname,
+                        roles,
+                        words,
+                        subs,
+                        longs,
+                        hyphens,
+                        multi,
+                        lives,
+                        ..
+                    } = player;
+                    println!(
+                        "{peer_id} {nickname} [{}] -> lives: {lives} — words: {words} — subs: {subs} — longs: {longs} — hyphens: {hyphens} — multi: {multi}",
+                        roles.join(", ")
+                    );
+                }
+            }
+            "start" => bot.start_round_now().await,
+            "tail" => match tail.take() {
+                Some(handle) => {
+                    handle.abort();
+                    println!("stopped tailing events");
+                }
+                None => {
+                    let mut events = EVENTS.subscribe();
+                    tail = Some(tokio::spawn(async move {
+                        loop {
+                            match events.recv().await {
+                                Ok(event) => println!("{event}"),
+                                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                    println!("... skipped {skipped} events")
+                                }
+                                Err(broadcast::error::RecvError::Closed) => break,
+                            }
+                        }
+                    }));
+                    println!("tailing events, run `tail` again to stop");
+                }
+            },
+            "help" => println!("{USAGE}"),
+            _ => println!("unknown command `{cmd}`\n{USAGE}"),
+        }
+    }
+    Ok(())
+}
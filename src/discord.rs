@@ -0,0 +1,290 @@
+use reqwest::header::AUTHORIZATION;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+use crate::bot::BotHandle;
+use crate::command::{describe_error, Caller, CommandParserTrait, Context};
+use crate::config::DiscordConfig;
+use crate::metrics::METRICS;
+use crate::roles::RoleTable;
+use crate::types::Auth;
+
+// discord refuses messages longer than this
+const MAX_MESSAGE_LENGTH: usize = 2000;
+// longest wait between two failed polls
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub id: String,
+    pub content: String,
+    pub author: User,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub username: String,
+    #[serde(default)]
+    pub bot: bool,
+}
+
+/// Thin client over the parts of the discord REST API used by the bridge.
+#[derive(Debug, Clone)]
+pub struct DiscordClient {
+    http: reqwest::Client,
+    api_url: String,
+    token: String,
+}
+
+impl DiscordClient {
+    pub fn new(api_url: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.into(),
+            token: token.into(),
+        }
+    }
+
+    pub async fn send_message(&self, channel_id: &str, content: &str) -> anyhow::Result<()> {
+        self.http
+            .post(format!("{}/channels/{channel_id}/messages", self.api_url))
+            .header(AUTHORIZATION, format!("Bot {}", self.token))
+            .json(&json!({
+                "content": truncate(content, MAX_MESSAGE_LENGTH),
+                // never ping anyone with mirrored messages
+                "allowed_mentions": { "parse": [] },
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Messages of the channel posted after `after`, oldest first.
+    pub async fn messages_after(
+        &self,
+        channel_id: &str,
+        after: Option<&str>,
+    ) -> anyhow::Result<Vec<Message>> {
+        let mut request = self
+            .http
+            .get(format!("{}/channels/{channel_id}/messages", self.api_url))
+            .header(AUTHORIZATION, format!("Bot {}", self.token))
+            .query(&[("limit", "50")]);
+        if let Some(after) = after {
+            request = request.query(&[("after", after)]);
+        }
+        let mut messages = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<Message>>()
+            .await?;
+        // discord answers newest first
+        messages.reverse();
+        Ok(messages)
+    }
+}
+
+/// Sending half of the bridge, messages are posted to the channel in the background.
+#[derive(Debug, Clone)]
+pub struct DiscordBridge {
+    outbox: mpsc::Sender<String>,
+}
+
+impl DiscordBridge {
+    pub fn send(&self, message: String) {
+        if let Err(err) = self.outbox.try_send(message) {
+            tracing::warn!("dropped discord message: {err}");
+        }
+    }
+}
+
+/// Starts mirroring to the configured channel and listening to its commands.
+///
+/// Authors get the roles `role_table` gives their discord id, as they would in the room.
+pub fn start(
+    config: DiscordConfig,
+    role_table: Arc<RoleTable>,
+    bot: Arc<BotHandle>,
+    notifier: Arc<Notify>,
+) -> DiscordBridge {
+    let client = DiscordClient::new(&config.api_url, &config.token);
+    let (outbox, mut receiver) = mpsc::channel::<String>(512);
+
+    let sender = client.clone();
+    let channel_id = config.channel_id.clone();
+    tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if let Err(err) = sender.send_message(&channel_id, &message).await {
+                tracing::warn!("failed to post to discord: {err}");
+            }
+        }
+    });
+
+    tokio::spawn(listen(client, config, role_table, bot, notifier));
+
+    DiscordBridge { outbox }
+}
+
+async fn listen(
+    client: DiscordClient,
+    config: DiscordConfig,
+    role_table: Arc<RoleTable>,
+    bot: Arc<BotHandle>,
+    notifier: Arc<Notify>,
+) {
+    let poll_interval = Duration::from_millis(config.poll_interval_ms);
+    let mut backoff = poll_interval;
+    // only answer to messages posted after the bot started
+    let mut last_id = loop {
+        match client.messages_after(&config.channel_id, None).await {
+            Ok(mut messages) => break messages.pop().map(|message| message.id),
+            Err(err) => {
+                tracing::warn!("failed to poll discord, retrying in {backoff:?}: {err}");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    };
+    let mut interval = tokio::time::interval(poll_interval);
+    loop {
+        interval.tick().await;
+        let messages = match client
+            .messages_after(&config.channel_id, last_id.as_deref())
+            .await
+        {
+            Ok(messages) => messages,
+            Err(err) => {
+                tracing::warn!("failed to poll discord: {err}");
+                continue;
+            }
+        };
+        for message in messages {
+            last_id = Some(message.id.clone());
+            if message.author.bot {
+                continue;
+            }
+            let Some(reply) = on_message(&message, &config, &role_table, &bot, &notifier).await
+            else {
+                continue;
+            };
+            if let Err(err) = client.send_message(&config.channel_id, &reply).await {
+                tracing::warn!("failed to answer on discord: {err}");
+            }
+        }
+    }
+}
+
+async fn on_message(
+    message: &Message,
+    config: &DiscordConfig,
+    role_table: &RoleTable,
+    bot: &Arc<BotHandle>,
+    notifier: &Arc<Notify>,
+) -> Option<String> {
+    let content = message.content.strip_prefix('!')?;
+    // the channel is shared, others may use `!` for their own bots
+    if !config.authorised_users.contains(&message.author.id) {
+        return None;
+    }
+    let auth = Auth {
+        id: message.author.id.clone(),
+        service: "discord".to_string(),
+        username: message.author.username.clone(),
+    };
+    let roles = role_table.resolve(&[], Some(&auth));
+    let cmd = match content.parse_command(&roles, &format!("discord:{}", auth.id)) {
+        Ok(cmd) => cmd,
+        Err(err) => return Some(describe_error(&err, bot.get_locale().await)),
+    };
+    METRICS.commands.inc(cmd.name());
+    let ctx = Context {
+        caller: Caller {
+            nickname: message.author.username.clone(),
+            peer_id: None,
+            roles,
+        },
+        bot: Arc::clone(bot),
+        notifier: Arc::clone(notifier),
+    };
+    let reply = cmd.dispatch(&ctx).await;
+    Some(reply.unwrap_or_else(|| "done".to_string()))
+}
+
+fn truncate(content: &str, max: usize) -> &str {
+    match content.char_indices().nth(max) {
+        Some((index, _)) => &content[..index],
+        None => content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{read_request, write_response, Request, Response};
+    use tokio::net::TcpListener;
+
+    // answers a single request with `response`, handing the request back
+    async fn stand_in(response: Response) -> (String, tokio::task::JoinHandle<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await.unwrap();
+            write_response(&mut stream, response).await.unwrap();
+            request
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn posts_messages_without_mentions() {
+        let (url, server) = stand_in(Response::new(200, "application/json", "{}")).await;
+        let client = DiscordClient::new(url, "secret");
+        client.send_message("42", "hi @everyone").await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/channels/42/messages");
+        assert_eq!(request.header("authorization"), Some("Bot secret"));
+        let body = serde_json::from_slice::<serde_json::Value>(&request.body).unwrap();
+        assert_eq!(body["content"], "hi @everyone");
+        assert_eq!(body["allowed_mentions"]["parse"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn reads_messages_oldest_first() {
+        let messages = json!([
+            { "id": "3", "content": "!search ab", "author": { "id": "7", "username": "b" } },
+            { "id": "2", "content": "hello", "author": { "id": "8", "username": "a", "bot": true } },
+        ]);
+        let (url, server) =
+            stand_in(Response::new(200, "application/json", messages.to_string())).await;
+        let client = DiscordClient::new(url, "secret");
+        let messages = client.messages_after("42", Some("1")).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/channels/42/messages?limit=50&after=1");
+        assert_eq!(messages[0].id, "2");
+        assert!(messages[0].author.bot);
+        assert_eq!(messages[1].content, "!search ab");
+    }
+
+    #[tokio::test]
+    async fn surfaces_api_errors() {
+        let (url, _server) = stand_in(Response::text(404, "unknown channel")).await;
+        let client = DiscordClient::new(url, "secret");
+        assert!(client.send_message("42", "hi").await.is_err());
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        assert_eq!(truncate("héllo", 2), "hé");
+        assert_eq!(truncate("hi", 5), "hi");
+    }
+}
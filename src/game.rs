@@ -8,8 +8,10 @@ use std::sync::Arc;
 
 use crate::{
     bot::BotHandle,
+    i18n::Message,
+    metrics::METRICS,
     text_payload,
-    types::{DataOnNextTurn, Player, PlayerStats},
+    types::{DataOnNextTurn, Player, PlayerStats, Setup},
 };
 
 pub fn on_set_milestone(
@@ -20,7 +22,14 @@ pub fn on_set_milestone(
     async move {
         let payload = text_payload(payload);
         let name = serde_json::from_value::<String>(payload[0]["name"].clone())?;
+        let rules_locked = payload[0]["rulesLocked"].as_bool().unwrap_or_default();
+        bot.set_milestone(name.clone(), rules_locked).await;
         if name.as_str() == "seating" {
+            // the round is over, keep the stats of its players
+            let winner = payload[0]["lastRound"]["winner"]["nickname"]
+                .as_str()
+                .map(String::from);
+            bot.record_game(winner).await;
             let _ = game_socket.emit("joinRound", "").await;
         }
         if let Ok(current_player_peer_id) =
@@ -30,6 +39,7 @@ pub fn on_set_milestone(
                 let syllable = serde_json::from_value::<String>(payload[0]["syllable"].clone())?;
                 bot.set_syllable(syllable.clone()).await;
                 let word = bot.get_single_word(syllable).await;
+                METRICS.word_submitted();
                 let _ = game_socket
                     .emit("setWord", vec![json!(word.clone()), json!(true)])
                     .await;
@@ -40,6 +50,37 @@ pub fn on_set_milestone(
     .boxed()
 }
 
+pub fn on_setup(
+    payload: Payload,
+    _game_socket: Client,
+    bot: Arc<BotHandle>,
+) -> Pin<Box<dyn futures_util::Future<Output = anyhow::Result<()>> + Send + 'static>> {
+    async move {
+        // without it the rules cannot be changed, which is no reason to leave the game
+        match serde_json::from_value::<Setup>(text_payload(payload)[0].clone()) {
+            Ok(setup) => bot.set_setup(setup).await,
+            Err(err) => tracing::warn!("failed to read game setup: {err}"),
+        }
+        Ok(())
+    }
+    .boxed()
+}
+
+pub fn on_set_leader_peer(
+    payload: Payload,
+    _game_socket: Client,
+    bot: Arc<BotHandle>,
+) -> Pin<Box<dyn futures_util::Future<Output = anyhow::Result<()>> + Send + 'static>> {
+    async move {
+        let peer_id = text_payload(payload)[0]
+            .as_u64()
+            .ok_or_else(|| anyhow!("failed to extract leader peer id"))?;
+        bot.set_leader(peer_id).await;
+        Ok(())
+    }
+    .boxed()
+}
+
 pub fn on_set_player_word(
     payload: Payload,
     _game_socket: Client,
@@ -63,6 +104,7 @@ pub fn on_next_turn(
         bot.set_syllable(details.syllable.clone()).await;
         if bot.get_peer_id().await == details.player_peer_id {
             let word = bot.get_single_word(details.syllable).await;
+            METRICS.word_submitted();
             let _ = game_socket
                 .emit("setWord", vec![json!(word), json!(true)])
                 .await;
@@ -124,6 +166,7 @@ pub fn on_fail_word(
             .as_u64()
             .ok_or_else(|| anyhow!("failed to extract peer id"))?;
         let reason = from_value::<String>(payload[1].clone())?;
+        METRICS.failed_words.inc(&reason);
 
         let bot_handle2 = Arc::clone(&bot_handle);
 
@@ -135,6 +178,7 @@ pub fn on_fail_word(
             let word = bot_handle
                 .get_single_word(bot_handle.get_syllable().await)
                 .await;
+            METRICS.word_submitted();
             let _ = game_socket
                 .emit("setWord", vec![json!(word), json!(true)])
                 .await;
@@ -158,23 +202,16 @@ pub fn on_lives_lost(
             .as_u64()
             .ok_or_else(|| anyhow!("failed to extract lives"))?;
         if lives == 0 {
-            if let Some(PlayerStats {
-                nickname,
-                words,
-                subs,
-                longs,
-                hyphens,
-                multi,
-                lives,
-                ..
-            }) = bot.get_player(peer_id).await
-            {
-                bot.set_chat(format!(
-                    "Well played {nickname}! \
-                lives: {lives} — words: {words} — subs: {subs} \
-                — longs: {longs} — hyphens: {hyphens} — multi: {multi}"
-                ))
-                .await;
+            if let Some(stats) = bot.get_player(peer_id).await {
+                let locale = bot.get_locale().await;
+                let message = locale.format(
+                    Message::WellPlayed,
+                    &[
+                        ("nickname", &stats.nickname),
+                        ("stats", &locale.stat_line(&stats)),
+                    ],
+                );
+                bot.announce(message).await;
             }
         }
         Ok(())
@@ -194,8 +231,12 @@ pub fn on_bonus_alphabet_completed(
             .ok_or_else(|| anyhow!("failed to extract peer id"))?;
         if let Some(PlayerStats { nickname, .. }) = bot.get_player(peer_id).await {
             let lives_count = bot.increment_lives(peer_id).await;
-            bot.set_chat(format!("{nickname} has gained a life ({lives_count})"))
-                .await;
+            let locale = bot.get_locale().await;
+            let message = locale.format(
+                Message::GainedLife,
+                &[("nickname", &nickname), ("lives", &lives_count)],
+            );
+            bot.announce(message).await;
         }
         Ok(())
     }
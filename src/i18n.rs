@@ -0,0 +1,382 @@
+use anyhow::anyhow;
+use serde::Deserialize;
+use std::fmt::Display;
+
+use crate::types::PlayerStats;
+
+/// Language of the room, used for the messages of the bot and command descriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Locale {
+    /// Two letter language code, as used by the `description(...)` of commands.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Fr => "fr",
+        }
+    }
+
+    /// Language sent to jklm when joining a room.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::En => "en-US",
+            Self::Fr => "fr-FR",
+        }
+    }
+
+    /// `message` in this language, with its `{placeholders}` filled from `args`.
+    pub fn format(self, message: Message, args: &[(&str, &dyn Display)]) -> String {
+        fill(message.template(self), args)
+    }
+
+    pub fn text(self, message: Message) -> String {
+        self.format(message, &[])
+    }
+
+    /// Counters of `stats`, as shown by `!stats` and at the end of a game.
+    pub fn stat_line(self, stats: &PlayerStats) -> String {
+        self.format(
+            Message::StatLine,
+            &[
+                ("lives", &stats.lives),
+                ("words", &stats.words),
+                ("subs", &stats.subs),
+                ("longs", &stats.longs),
+                ("hyphens", &stats.hyphens),
+                ("multi", &stats.multi),
+            ],
+        )
+    }
+}
+
+/// `template` with its `{placeholders}` filled from `args`, for templates read from the config.
+///
+/// Done in one pass, so values containing `{placeholders}` are kept as they are.
+pub fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[1..end];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                text.push_str(&value.to_string());
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+impl std::str::FromStr for Locale {
+    type Err = anyhow::Error;
+    // `fr`, `fr-FR` and `fr_FR` all select french
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.split(['-', '_']).next().unwrap_or_default();
+        match language.to_lowercase().as_str() {
+            "en" => Ok(Self::En),
+            "fr" => Ok(Self::Fr),
+            _ => Err(anyhow!(
+                "unsupported language `{s}`, expected one of: en, fr"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Every message the bot sends to the room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Greeting,
+    Sayonara,
+    NoResult,
+    Results,
+    NoPage,
+    NoSearch,
+    InvalidRegex,
+    ExpensiveRegex,
+    SearchTimedOut,
+    StatLine,
+    Stats,
+    NoStats,
+    WellPlayed,
+    GainedLife,
+    Leaders,
+    Rank,
+    NobodyYet,
+    RankOf,
+    Unranked,
+    Summary,
+    Nobody,
+    SummaryMvp,
+    SummaryLongest,
+    SummarySubs,
+    SummaryHyphens,
+    SummaryMulti,
+    SummaryLives,
+    CommandList,
+    Help,
+    HelpAliases,
+    HelpRoles,
+    UnknownCommand,
+    DidYouMean,
+    NotEligible,
+    CoolingDown,
+    MissingArgument,
+    InvalidArgument,
+    TooManyArguments,
+    Perk,
+    PerkLong,
+    PerkHyphen,
+    PerkSub,
+    PerkMulti,
+    Prefix,
+    PrefixSet,
+    Definition,
+    NoDefinition,
+    RulesChanged,
+    NotLeader,
+    RoundOngoing,
+    BombOutOfRange,
+    Kicked,
+    Banned,
+    Unbanned,
+    NotBanned,
+    Bans,
+    NoBans,
+    Muted,
+    Unmuted,
+    NoChatter,
+    CannotModerateBot,
+    Outranked,
+}
+
+impl Message {
+    fn template(self, locale: Locale) -> &'static str {
+        let (en, fr) = match self {
+            Self::Greeting => ("Hey, {nickname}!", "Salut, {nickname} !"),
+            Self::Sayonara => ("sayonara!", "sayonara !"),
+            Self::NoResult => (
+                "No result found for: {query}",
+                "Aucun résultat pour : {query}",
+            ),
+            Self::Results => (
+                "results({count}) — page {page}/{pages}: {words}",
+                "résultats({count}) — page {page}/{pages} : {words}",
+            ),
+            Self::NoPage => (
+                "no page {page}, there are {pages}",
+                "pas de page {page}, il y en a {pages}",
+            ),
+            Self::NoSearch => (
+                "nothing to continue, use !search first",
+                "rien à continuer, utilise d'abord !search",
+            ),
+            Self::InvalidRegex => ("invalid regex: {error}", "regex invalide : {error}"),
+            Self::ExpensiveRegex => ("too expensive regex", "regex trop coûteuse"),
+            Self::SearchTimedOut => (
+                "search took too long, try a narrower query",
+                "recherche trop longue, essaie une requête plus précise",
+            ),
+            Self::StatLine => (
+                "lives: {lives} — words: {words} — subs: {subs} — longs: {longs} — hyphens: {hyphens} — multi: {multi}",
+                "vies : {lives} — mots : {words} — subs : {subs} — longs : {longs} — tirets : {hyphens} — multi : {multi}",
+            ),
+            Self::Stats => ("Stats for {nickname} -> {stats}", "Stats de {nickname} -> {stats}"),
+            Self::NoStats => (
+                "no stats found for {nickname}",
+                "aucune stat trouvée pour {nickname}",
+            ),
+            Self::WellPlayed => (
+                "Well played {nickname}! {stats}",
+                "Bien joué {nickname} ! {stats}",
+            ),
+            Self::GainedLife => (
+                "{nickname} has gained a life ({lives})",
+                "{nickname} a gagné une vie ({lives})",
+            ),
+            Self::Leaders => (
+                "Top {category} ({window}) -> room: {room} — all rooms: {overall}",
+                "Top {category} ({window}) -> salon : {room} — tous les salons : {overall}",
+            ),
+            Self::Rank => (
+                "Rank of {nickname} in {category} ({window}) -> room: {room} — all rooms: {overall}",
+                "Rang de {nickname} en {category} ({window}) -> salon : {room} — tous les salons : {overall}",
+            ),
+            Self::NobodyYet => ("nobody yet", "personne pour l'instant"),
+            Self::RankOf => ("#{rank} of {count} ({total})", "#{rank} sur {count} ({total})"),
+            Self::Unranked => ("unranked", "non classé"),
+            Self::Summary => ("Game over! winner: {winner}", "Partie terminée ! gagnant : {winner}"),
+            Self::Nobody => ("nobody", "personne"),
+            Self::SummaryMvp => (
+                "mvp: {nickname} ({count} words)",
+                "mvp : {nickname} ({count} mots)",
+            ),
+            Self::SummaryLongest => (
+                "longest: {word} ({nickname})",
+                "le plus long : {word} ({nickname})",
+            ),
+            Self::SummarySubs => ("subs: {nickname} ({count})", "subs : {nickname} ({count})"),
+            Self::SummaryHyphens => (
+                "hyphens: {nickname} ({count})",
+                "tirets : {nickname} ({count})",
+            ),
+            Self::SummaryMulti => ("multi: {nickname} ({count})", "multi : {nickname} ({count})"),
+            Self::SummaryLives => ("lives: {lives}", "vies : {lives}"),
+            Self::CommandList => (
+                "commands: {commands} — use !help <command> for details",
+                "commandes : {commands} — !help <commande> pour les détails",
+            ),
+            Self::Help => (
+                "{description} — usage: {usage}",
+                "{description} — usage : {usage}",
+            ),
+            Self::HelpAliases => ("aliases: {aliases}", "alias : {aliases}"),
+            Self::HelpRoles => ("roles: {roles}", "rôles : {roles}"),
+            Self::UnknownCommand => ("unknown command", "commande inconnue"),
+            Self::DidYouMean => (
+                "unknown command — did you mean {commands}?",
+                "commande inconnue — voulais-tu dire {commands} ?",
+            ),
+            Self::NotEligible => (
+                "you are not eligible to use this command",
+                "tu n'as pas le droit d'utiliser cette commande",
+            ),
+            Self::CoolingDown => (
+                "wait {seconds}s before using !{command} again",
+                "attends {seconds}s avant de réutiliser !{command}",
+            ),
+            Self::MissingArgument => (
+                "missing argument <{argument}> — usage: {usage}",
+                "argument <{argument}> manquant — usage : {usage}",
+            ),
+            Self::InvalidArgument => (
+                "invalid argument <{argument}>: {error} — usage: {usage}",
+                "argument <{argument}> invalide : {error} — usage : {usage}",
+            ),
+            Self::TooManyArguments => (
+                "too many arguments — usage: {usage}",
+                "trop d'arguments — usage : {usage}",
+            ),
+            Self::Perk => (
+                "{nickname} has placed {perks}: {word}",
+                "{nickname} a placé {perks} : {word}",
+            ),
+            Self::PerkLong => ("a long ({count})", "un long ({count})"),
+            Self::PerkHyphen => ("a hyphen ({count})", "un tiret ({count})"),
+            Self::PerkSub => ("a sn ({count})", "un sn ({count})"),
+            Self::PerkMulti => ("a multi ({count})", "un multi ({count})"),
+            Self::Prefix => (
+                "commands start with {prefix}",
+                "les commandes commencent par {prefix}",
+            ),
+            Self::RulesChanged => ("rules changed — {rules}", "règles changées — {rules}"),
+            Self::NotLeader => (
+                "I must lead the room to change its rules",
+                "je dois être chef du salon pour changer ses règles",
+            ),
+            Self::RoundOngoing => (
+                "rules can only be changed between rounds",
+                "les règles ne changent qu'entre deux manches",
+            ),
+            Self::BombOutOfRange => (
+                "the bomb must last between {min}s and {max}s",
+                "la bombe doit durer entre {min}s et {max}s",
+            ),
+            Self::Definition => ("{word}: {definition}", "{word} : {definition}"),
+            Self::NoDefinition => (
+                "no definition found for {word}",
+                "aucune définition trouvée pour {word}",
+            ),
+            Self::Kicked => ("kicked {nickname}", "{nickname} a été expulsé"),
+            Self::Banned => ("banned {nickname}", "{nickname} a été banni"),
+            Self::Unbanned => ("unbanned {nickname}", "{nickname} n'est plus banni"),
+            Self::NotBanned => ("{nickname} is not banned", "{nickname} n'est pas banni"),
+            Self::Bans => ("banned: {bans}", "bannis : {bans}"),
+            Self::NoBans => ("nobody is banned", "personne n'est banni"),
+            Self::Muted => (
+                "ignoring the commands of {nickname} for {minutes} min",
+                "les commandes de {nickname} sont ignorées pendant {minutes} min",
+            ),
+            Self::Unmuted => (
+                "{nickname} can use commands again",
+                "{nickname} peut de nouveau utiliser les commandes",
+            ),
+            Self::NoChatter => (
+                "nobody called {nickname} is in the room",
+                "personne ne s'appelle {nickname} dans le salon",
+            ),
+            Self::CannotModerateBot => (
+                "the bot cannot moderate itself",
+                "le bot ne peut pas se modérer lui-même",
+            ),
+            Self::Outranked => (
+                "you cannot moderate {nickname}, who ranks as high as you",
+                "tu ne peux pas modérer {nickname}, qui a un rang aussi élevé que le tien",
+            ),
+            Self::PrefixSet => (
+                "commands now start with {prefix}",
+                "les commandes commencent désormais par {prefix}",
+            ),
+        };
+        match locale {
+            Locale::En => en,
+            Locale::Fr => fr,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_language_from_tags() {
+        assert_eq!("fr-FR".parse::<Locale>().unwrap(), Locale::Fr);
+        assert_eq!("EN".parse::<Locale>().unwrap(), Locale::En);
+        assert!("de".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        let args: &[(&str, &dyn Display)] = &[("nickname", &"mika"), ("lives", &3)];
+        assert_eq!(
+            Locale::En.format(Message::GainedLife, args),
+            "mika has gained a life (3)"
+        );
+        assert_eq!(
+            Locale::Fr.format(Message::GainedLife, args),
+            "mika a gagné une vie (3)"
+        );
+    }
+
+    #[test]
+    fn fills_values_as_they_are() {
+        let args: &[(&str, &dyn Display)] = &[("nickname", &"{word}"), ("word", &"abc")];
+        assert_eq!(fill("{nickname} played {word}", args), "{word} played abc");
+        assert_eq!(fill("{unknown} {word", args), "{unknown} {word");
+    }
+}
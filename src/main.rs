@@ -10,20 +10,42 @@ use tokio::sync::Notify;
 
 use crate::command::CommandParserTrait;
 
+pub mod api;
 pub mod bot;
+pub mod chat;
 pub mod command;
+pub mod config;
+pub mod console;
+pub mod discord;
 mod game;
+pub mod http;
+pub mod i18n;
+pub mod metrics;
+pub mod moderation;
+pub mod perks;
+pub mod query;
+pub mod roles;
+pub mod rules;
+pub mod stats;
+pub mod summary;
 pub mod types;
 pub mod utils;
 
 use bot::BotHandle;
-use command::Command;
+use command::{Caller, Context, UnknownCommand};
+use config::{CommandsConfig, Config, CONFIG_PATH};
+use discord::DiscordBridge;
 use game::*;
+use i18n::{Locale, Message};
+use metrics::METRICS;
+use roles::{Role, RoleTable};
 use types::*;
 use utils::*;
 
 // timeout to wait for socket to receive response (in seconds).
 const TIMEOUT: u64 = 5;
+// name the bot answers to, as in `kaoruko, search ab`
+const MENTION_NAME: &str = "kaoruko";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -36,6 +58,28 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::subscriber::set_global_default(subscriber)?;
 
+    let config = Config::load(CONFIG_PATH)?;
+
+    // `kaoruko commands [markdown|json] [language]` prints the command reference and exits
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {}
+        Some("commands") => {
+            let format = args.next().unwrap_or_else(|| "markdown".to_string());
+            let locale = match args.next() {
+                Some(language) => language.parse()?,
+                None => config.room.language,
+            };
+            println!("{}", command::reference(&format, locale)?);
+            return Ok(());
+        }
+        Some(subcommand) => {
+            return Err(anyhow::anyhow!(
+                "unknown subcommand `{subcommand}`, expected: commands"
+            ))
+        }
+    }
+
     tokio::spawn(async move {
         let token = create_user_token().unwrap();
         let (host, room_code) = start_new_room(None, false, &token).await?;
@@ -43,27 +87,102 @@ async fn main() -> anyhow::Result<()> {
         // TODO: we can get rid of this.
         let room_code: &'static str = Box::leak(Box::new(room_code));
 
-        let bot = Arc::new(BotHandle::new());
+        let locale = config.room.language;
+        let chat = chat::start(config.chat);
+        let bot = Arc::new(BotHandle::new(room_code, config.room, config.perks, chat)?);
         let bot2 = Arc::clone(&bot);
         let bot3 = Arc::clone(&bot);
+        let bot4 = Arc::clone(&bot);
+        let bot5 = Arc::clone(&bot);
+
+        if config.metrics.enabled {
+            let bot = Arc::clone(&bot);
+            tokio::spawn(async move {
+                if let Err(err) = metrics::serve(config.metrics.address, bot).await {
+                    tracing::error!("metrics endpoint stopped: {err}");
+                }
+            });
+        }
 
         // useful for halting the bot
         let notifier = Arc::new(Notify::new());
         let notifier2 = Arc::clone(&notifier);
 
+        if config.console.enabled {
+            let bot = Arc::clone(&bot);
+            let notifier = Arc::clone(&notifier);
+            tokio::spawn(async move {
+                if let Err(err) = console::run(bot, notifier).await {
+                    tracing::error!("console stopped: {err}");
+                }
+            });
+        }
+
+        if config.api.enabled {
+            let room = api::Room {
+                code: room_code,
+                bot: Arc::clone(&bot),
+                notifier: Arc::clone(&notifier),
+            };
+            tokio::spawn(async move {
+                if let Err(err) = api::serve(config.api, vec![room]).await {
+                    tracing::error!("control api stopped: {err}");
+                }
+            });
+        }
+
+        let role_table = Arc::new(config.roles);
+        let role_table2 = Arc::clone(&role_table);
+        let commands_config = Arc::new(config.commands);
+
+        let discord = if config.discord.enabled {
+            let bridge = discord::start(
+                config.discord,
+                Arc::clone(&role_table),
+                Arc::clone(&bot),
+                Arc::clone(&notifier),
+            );
+            bot.set_discord(bridge.clone()).await;
+            Some(bridge)
+        } else {
+            None
+        };
+
         let socket = ClientBuilder::new(host)
-            .reconnect(false)
+            // reconnected when the network or jklm drops it, joining the room again on connect
+            .reconnect(true)
+            .reconnect_on_disconnect(true)
             .transport_type(TransportType::Websocket)
             .on(Event::Connect, move |payload, socket| {
                 let bot2 = Arc::clone(&bot2);
+                METRICS.connected("room");
                 tracing::info!("Playing at https://jklm.fun/{room_code}");
-                on_connect(payload, socket, room_code, bot2, token.clone())
+                on_connect(payload, socket, room_code, locale, bot2, token.clone())
             })
             .on("chat", move |payload, socket| {
                 let bot3 = Arc::clone(&bot);
-                on_chat(payload, socket, bot3, Arc::clone(&notifier))
+                on_chat(
+                    payload,
+                    socket,
+                    bot3,
+                    Arc::clone(&notifier),
+                    discord.clone(),
+                    Arc::clone(&role_table),
+                    Arc::clone(&commands_config),
+                )
+            })
+            .on("chatterAdded", move |payload, socket| {
+                on_chatter_added(
+                    payload,
+                    socket,
+                    Arc::clone(&bot4),
+                    locale,
+                    Arc::clone(&role_table2),
+                )
+            })
+            .on("chatterRemoved", move |payload, socket| {
+                on_chatter_removed(payload, socket, Arc::clone(&bot5))
             })
-            .on("chatterAdded", on_chatter_added)
             .connect()
             .await
             .expect("Connection failed");
@@ -85,6 +204,7 @@ fn on_connect(
     _paylod: Payload,
     socket: Client,
     room_code: &'static str,
+    locale: Locale,
     bot: Arc<BotHandle>,
     token: String,
 ) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + 'static>> {
@@ -96,7 +216,7 @@ fn on_connect(
                     "roomCode": room_code,
                     "userToken": token,
                     "picture": "/9j/4AAQSkZJRgABAQEASABIAAD/4QC8RXhpZgAASUkqAAgAAAAGABIBAwABAAAAAQAAABoBBQABAAAAVgAAABsBBQABAAAAXgAAACgBAwABAAAAAgAAABMCAwABAAAAAQAAAGmHBAABAAAAZgAAAAAAAABIAAAAAQAAAEgAAAABAAAABgAAkAcABAAAADAyMTABkQcABAAAAAECAwAAoAcABAAAADAxMDABoAMAAQAAAP//AAACoAQAAQAAAAACAAADoAQAAQAAAAACAAAAAAAA//4AK0pQRyByZXNpemVkIHdpdGggaHR0cHM6Ly9lemdpZi5jb20vcmVzaXpl/9sAQwAFAwQEBAMFBAQEBQUFBgcMCAcHBwcPCwsJDBEPEhIRDxERExYcFxMUGhURERghGBodHR8fHxMXIiQiHiQcHh8e/9sAQwEFBQUHBgcOCAgOHhQRFB4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4e/8AAEQgASwBLAwEiAAIRAQMRAf/EABwAAAIDAQEBAQAAAAAAAAAAAAYHBAUIAwABAv/EADkQAAIBAwIDBgUBBgYDAAAAAAECAwQFEQAhBhIxBxMiQVFhFEJxgZEyCCNSgqGxFRYzosHRYnLx/8QAGQEAAwEBAQAAAAAAAAAAAAAAAwQFAgYB/8QAJBEAAgICAgEDBQAAAAAAAAAAAQIAAxEhEjEEEyJRMjNBkfD/2gAMAwEAAhEDEQA/ANJ1kuMgHVRNUqHI5tVNwuc/xNRTVNTy87s8fKu4iwBj2OTn6aq4KpYXK90Qd0TLDndQMgD7521RRJPZ9z52hcf2fgy1rV3CQSzS5ENMjgO+Ad8bkDyzjGTrMHGnavxpxD8RHVXaWKjkLD4KlIijVD8pI8TbbbnXfiq5T8c8Q119rlNOkTGmpIHO4CsxJP8A6gn7589BldbpVRGxyCXJXPULnGT7k5/B0NjnqN11hRvuRxebtI/LLXV0qAYWN6tyAOuOuiThnjLiq0yRmz8QXSidGLLEKgshJ6+EnlOcdCN9Ds1umjqEIRgBgEAb4I6j++ptHTyMSkkeHTZsDY+4/HT2PtrIhMTT3ZN20w8TVdLab/FBQ3OoLRxNHzBJGUZ3zsObDYAOxGPMabRuCx7A6wtcaGopohXJnmRgJkB3wejfUbb+4OtG8AccvxPw+KySnNPNCVilHNkM3KDkaKgDHEWu9gyI34LoCf16npcfAPFpbU908W741ZR3XwDxa0aYBbpT19YWnLbZzjON9B/aFfZbNwvWT0ZEdfNE6007KSUfA6f+WOn0J8tSK66BWI5t9LntcuFTVWekWOVlp46oGZQcBtvD/X++mXQhCYCpg1gBgXDzBRCZGkIGGZiSWYnck+5ydSrNSm6F2dQERO7iJ/iaRIl/HOT9TofgrHYyOu/M7FT7Dwj+pOmhwfwVcLrbKirWlmEbUz/Dju27vvEnUqCANwVCtvkdSOmkGYASygyZPtfCLVlJVQQwhquIQyQoerl49l+7w8n1kGl/VxOlxAirIA6rsZPD3sZ3BBPzDlzj1DDyOnzRWPiOskjrrDaJIu5DLyyOIA8DkN3J5t0kiYLynBAaFT0Y66cYcFRz1NJeGqqSy3GWQPV0c7EUzTE5cxSIwMbt+oheYBjn+LmFyhSoOpm+41RVW35uQYdM7Mhz/bJ0Xdj11qYq2tpRVF6VYucIRuWLDDe+2RnQZxvbms3F1dbaeqWpSGdhBIHVhIh3AyMAn8b5G2p/AV1p7ZPMJEMZm5Y2Y/JgkgEdQDnr+fPTVBHMZiPkqfTYAbjvp7nhv1b/AF1ZR3U8g8R/Ol1Fcn585A38jnVjHcTyDxHVU1iQxZOFxuBVz4t86GL+8FbAUrCfh0BYgHGTjA39d/xn11+62q55Dvt7araiJasxxyboHBYA/q9B+ca1ZXlCBPKbAHBbqD1DTPU3GnpKKFvh+9SnVyMAt5Ln8k+mtRdmlpSp4PhprtcKypj72WOOKnqHhihCSsv7soQxBIJySTrN9XxQLZfre1FHBJ8AWPIU8CZHlg/q6H2wPpp99g/HNhvzf5YhRrfVUcaikSeQMagcuWORgFwxZiB1B9jqFcvE4nU+G6uvJtExzWxI6O3pTpJI0UKgBp5WkbGfNjkn76g3+kp5KiOqloqeaeIFY5JIgzID15SRt9tJmq467Z7Q0lBXWbhyOSKSSP4yZJWM3KxAYRowABGCPUEa9w+na5xrxFbHul8oqeyQ1kctUlBSmmLIp5iM7lugGC3zdNL+oDqOr47qPUP0z5209n9qv3c3aOEUkqgpUPTxAYHXvWA6gb8x8hg+R0la+yxITHBcYq+KM8iVKbBgDvv1IyP+Ro0/aT47pbxxhTcPWSfmo7XI61EsbeGWdwQQCOqqAV9CSdLjhO5UzTvR1MrwNzEqAMjmHUED++mfH459/Un+WSftDcJra8kNNHHI2WUYyepAJx/TGrRKohQObVRNNByH4aaGWM/MuD/u/wDmuqMWQNzKNvXV2pwy6nMXVsjEN3I7Mebm321waKeZTzh0hYYJVTuScYz76afYulnaSsS4WaKSeNwUr5VDooOwQ52U+YI653xtllX5ZGt88NKcSd2wjKLnkf5TgehwfbS19x5FRC0BVHI9zGl0pO6DVAQxq5IUcuOnl9tHXYNw21+4gkuJdkW3kVCsoz48gKPbof66q+MKOphtlkt1bC9PX0/xNNVRyDB7wytIj58w6uMH2Ppo9/ZKuVPRcT3ez1fMBUwI0IIJ8UbnK4G+cN09tSH0DOjowWHxNJSPUQgpLTx3CFTskuO8X+YjDfff31UX671dRA9FT05oUccgUDc523xjb2Gitmppd2xn1Kkai1VNHISiBeVMFmfou/l66BhuhHlZAckTDHaBYJ7HxhUxSqRzMXGfUEn/AL/OnF2I3ThEcLVeLVb6e9pgmpSMCadXYcp5upKtuceS/XRX269lz8TWwXHh6YPfKN/9F2AWqQ7FM9EYfL5HcE+es28NrXW6seGQTUtTC8kbRuCjxshzg+YIyRplTmT7VzkCaruVj4UvMMs9ytdtkVV5mlRAko65PMuD6azrcoytxqVo3nhpxKwjjZgzIuThST5jodGth7QLg1FHRX8LU0XOGNfEn7yMDoJVHlkjxDrtqyv3ZinEd1lvlDVBIK1UlASTAJKjmI+pyfvppW/IkpVKNws6/cMeG7fTW/hhaNFHJJ+o+b+IAt9yfwBqXbLlLT0726qbIjfu436EY8j/AMHXqsBLfAq7AUJIHvtqDxaAlfUlNuZVY49eXrrR2dydnAkDtWoKSv4MuPfwQNURU5lpZHQEpKniAz6MAyke488aRNXa6qz3t2qUCVNHM0bnnw0bo5Uox+YZGxO+CNPvieRpLjwtSyENDVXOmE6EAiQABwD7cwB+2lD2rMfi6Wqye+q667PO2f1nvwMkdOgA0B9NLHgsTXgw14N4w4hW60Vlbiu6WWSuXNC1Ry1tDPvy8uJfGm4xs2x2ONstzh3iasn4jk4Y4nNGl1io1qQ9CWEVVCzFCwDeJGUggrkjcEHy0jqSkp63hXs/FVEJOa+0qknY4emVnGR6lFJ+mmNYiantzu7TnnMFtMURPyqZun+0aC0oLDbj6+UXDVgNXLN3XM3dRKu5MjDLcvqQBy/UnWYePxDHxItymnWSqqF5ZkU5EbkkkZ8yE5QT6nTE7fKmf/HeHo+9bkht1XURr5CRRs2PUaS3EbvLxfXwSMWipYFigXyRSqk/clmJPUk762ulizZN2PgSy4Lugtd5t1zrWAoiwM6EZBjCYZceeRgY8zp89nTT0fBlvglD0pCuwgO5hVnZlT+UED7aT/CtFSz8VcKU00CPC7IWQjYnvH/6H406LU7GiBLEku5J/nOmKlk7zrNgD+xmf//Z",
-                    "language": "en-US",
+                    "language": locale.tag(),
                     "nickname": "kaoruko ✨",
                 }),
                 Duration::from_secs(TIMEOUT),
@@ -125,9 +245,11 @@ fn on_connect_inner(
             bot.set_peer_id(room_details.self_peer_id).await;
             // bombparty game socket
             let game_socket = ClientBuilder::new(join_room(room_code).await.unwrap())
-                .reconnect(false)
+                .reconnect(true)
+                .reconnect_on_disconnect(true)
                 .transport_type(TransportType::Websocket)
                 .on(Event::Connect, move |payload, socket| {
+                    METRICS.connected("game");
                     on_game_socket_connect(payload, socket, room_code, token.clone())
                 })
                 .on_any(move |event, payload, socket| {
@@ -175,12 +297,17 @@ fn handle_game_socket(
     bot: Arc<BotHandle>,
 ) -> Pin<Box<dyn futures_util::Future<Output = anyhow::Result<()>> + Send + 'static>> {
     let bot_handle = Arc::clone(&bot);
-    match String::from(event).as_str() {
+    let event = String::from(event);
+    METRICS.events.inc(&event);
+    console::publish("game", &event, &payload);
+    match event.as_str() {
         "nextTurn" => on_next_turn(payload, socket, bot_handle),
         "setPlayerWord" => on_set_player_word(payload, socket, bot_handle),
         "correctWord" => on_correct_word(payload, socket, bot_handle),
         "failWord" => on_fail_word(payload, socket, bot_handle),
         "setMilestone" => on_set_milestone(payload, socket, bot_handle),
+        "setup" => on_setup(payload, socket, bot_handle),
+        "setLeaderPeer" => on_set_leader_peer(payload, socket, bot_handle),
         "addPlayer" => on_add_player(payload, socket, bot_handle),
         "livesLost" => on_lives_lost(payload, socket, bot),
         "bonusAlphabetCompleted" => on_bonus_alphabet_completed(payload, socket, bot),
@@ -191,16 +318,31 @@ fn handle_game_socket(
 fn on_chatter_added(
     payload: Payload,
     socket: Client,
+    bot: Arc<BotHandle>,
+    locale: Locale,
+    role_table: Arc<RoleTable>,
 ) -> Pin<Box<dyn futures_util::Future<Output = ()> + Send + 'static>> {
     async move {
+        METRICS.events.inc("chatterAdded");
+        console::publish("room", "chatterAdded", &payload);
         let NewChatter {
-            auth: Auth { id, .. },
+            auth,
             peer_id,
             nickname,
+            roles,
         } = NewChatter::from(text_payload(payload));
-        let _ = socket.emit("chat", format!("Hey, {nickname}!")).await;
-        // mods me, the developer.
-        if id == "988839581384323083" || id == "907638639314473060" {
+        let roles = role_table.resolve(&roles, Some(&auth));
+        // banned chatters are kicked right away
+        if bot
+            .add_chatter(peer_id, nickname.clone(), Some(auth.clone()), roles)
+            .await
+        {
+            return;
+        }
+        let greeting = locale.format(Message::Greeting, &[("nickname", &nickname)]);
+        bot.announce(greeting).await;
+        // mods developers and trusted users
+        if role_table.is_trusted(&auth) {
             let _ = socket
                 .emit_with_ack(
                     "setUserModerator",
@@ -214,84 +356,89 @@ fn on_chatter_added(
     .boxed()
 }
 
-// TODO: the parser is broken
+fn on_chatter_removed(
+    payload: Payload,
+    _socket: Client,
+    bot: Arc<BotHandle>,
+) -> Pin<Box<dyn futures_util::Future<Output = ()> + Send + 'static>> {
+    async move {
+        METRICS.events.inc("chatterRemoved");
+        console::publish("room", "chatterRemoved", &payload);
+        match text_payload(payload).first().and_then(|value| value.as_u64()) {
+            Some(peer_id) => bot.remove_chatter(peer_id).await,
+            None => tracing::warn!("chatterRemoved without a peer id"),
+        }
+    }
+    .boxed()
+}
+
 fn on_chat(
     payload: Payload,
-    socket: Client,
+    _socket: Client,
     bot: Arc<BotHandle>,
     notifier: Arc<Notify>,
+    discord: Option<DiscordBridge>,
+    role_table: Arc<RoleTable>,
+    commands_config: Arc<CommandsConfig>,
 ) -> Pin<Box<dyn futures_util::Future<Output = ()> + Send + 'static>> {
     async move {
         tokio::spawn(async move {
+            METRICS.events.inc("chat");
+            console::publish("room", "chat", &payload);
             let values = text_payload(payload);
             let chatter = serde_json::from_value::<Chatter>(values[0].clone())?;
             let message = serde_json::from_value::<String>(values[1].clone())?;
-            let bot_peer_id = bot.get_peer_id().await;
-            // TODO: move this parsing inside the function
-            let (cmd, query) = message.split_once(" ").unwrap_or((&message, ""));
-            if !cmd.starts_with("!") && bot_peer_id == chatter.peer_id {
-                return Ok::<(), anyhow::Error>(());
+            if let Some(discord) = &discord {
+                discord.send(format!("**{}**: {message}", chatter.nickname));
             }
-            let Some(cmd) = cmd.strip_prefix("!") else {
-                return Ok(());
+            let bot_peer_id = bot.get_peer_id().await;
+            let prefix = bot.get_prefix().await;
+            // the bot mentions itself in its own messages
+            let (cmd, mentioned) = match message.strip_prefix(prefix.as_str()) {
+                Some(cmd) => (cmd, false),
+                None if bot_peer_id == chatter.peer_id => return Ok::<(), anyhow::Error>(()),
+                None => match command::mentioned(&message, MENTION_NAME, &prefix) {
+                    Some(cmd) => (cmd, true),
+                    None => return Ok(()),
+                },
             };
-            match cmd.parse_command(
-                chatter.roles,
-                chatter.auth.as_ref(),
-                bot.get_room_creator().await,
-            ) {
-                Ok(cmd) => match cmd {
-                    Command::Search => {
-                        if query.is_empty() {
-                            let syllable = bot.get_syllable().await;
-                            let word = bot.get_words(syllable).await;
-                            let _ = socket.emit("chat", word).await;
-                            return Ok(());
-                        }
-                        let word = bot.get_words(query.to_string()).await;
-                        let _ = socket.emit("chat", word).await;
-                    }
-                    Command::Exit => {
-                        let _ = socket.emit("chat", "sayonara!").await;
-                        tokio::time::sleep(Duration::from_millis(500)).await;
-                        notifier.notify_one();
-                    }
-                    Command::Help => match query.parse::<Command>() {
-                        Ok(cmd) => {
-                            let _ = socket.emit("chat", cmd.help()).await;
-                        }
-                        Err(err) => {
-                            let _ = socket.emit("chat", err.to_string()).await;
-                        }
-                    },
-                    Command::StartNow => {
-                        bot.start_round_now().await;
-                    }
-                    // do not allow usage of this command on event seating
-                    Command::Stats => {
-                        let peer_id = chatter.peer_id;
-                        let player = bot.get_player(peer_id).await;
-                        if let Some(p) = player {
-                            let PlayerStats {
-                                nickname,
-                                words,
-                                subs,
-                                longs,
-                                hyphens,
-                                multi,
-                                lives,
-                                ..
-                            } = p;
-                            let message = format!("Stats for {nickname} -> lives: {lives} — words: {words} — subs: {subs} — longs: {longs} — hyphens: {hyphens} — multi: {multi}");
-                            let  _ = socket.emit("chat", message).await;
-                        } else {
-                            let  _ = socket.emit("chat", format!("no stats found for {}", &chatter.nickname)).await;                                
-                        }
+            if bot.is_muted(chatter.peer_id).await {
+                return Ok(());
+            }
+            let roles = role_table.resolve(&chatter.roles, chatter.auth.as_ref());
+            bot.set_chatter_roles(chatter.peer_id, roles.clone()).await;
+            // peer ids change when rejoining, accounts do not
+            let user = chatter
+                .auth
+                .as_ref()
+                .and_then(moderation::identity)
+                .unwrap_or_else(|| chatter.peer_id.to_string());
+            let parsed = cmd.parse_command(&roles, &user);
+            if let Ok(cmd) = &parsed {
+                METRICS.commands.inc(cmd.name());
+            }
+            match parsed {
+                Ok(cmd) => {
+                    let ctx = Context {
+                        caller: Caller {
+                            nickname: chatter.nickname,
+                            peer_id: Some(chatter.peer_id),
+                            roles,
+                        },
+                        bot,
+                        notifier,
+                    };
+                    if let Some(reply) = cmd.dispatch(&ctx).await {
+                        ctx.bot.set_chat(reply).await;
                     }
-                },
+                }
+                // chatters talk about the bot without calling commands
+                Err(err)
+                    if (commands_config.silent_unknown || mentioned)
+                        && err.downcast_ref::<UnknownCommand>().is_some() => {}
                 Err(err) => {
-                    let _ = socket.emit("chat", err.to_string()).await;
-                    return Ok(());
+                    let reply = command::describe_error(&err, bot.get_locale().await);
+                    bot.set_chat(reply).await;
                 }
             };
             Ok::<(), anyhow::Error>(())
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use crate::bot::BotHandle;
+use crate::http::{read_request, write_response, Response};
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+// upper bounds (in seconds) of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1,
+];
+
+/// Counter split by a single label.
+#[derive(Default)]
+pub struct LabeledCounter {
+    values: Mutex<BTreeMap<String, u64>>,
+}
+
+impl LabeledCounter {
+    pub fn inc(&self, label: &str) {
+        let mut values = self.values.lock().unwrap();
+        *values.entry(label.to_string()).or_default() += 1;
+    }
+
+    fn get(&self, label: &str) -> u64 {
+        self.values.lock().unwrap().get(label).copied().unwrap_or(0)
+    }
+
+    fn render(&self, out: &mut String, name: &str, label: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+        for (value, count) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(out, "{name}{{{label}=\"{}\"}} {count}", escape(value));
+        }
+    }
+}
+
+pub struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    // in nanoseconds, to keep it atomic
+    sum: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::default()),
+            count: AtomicU64::default(),
+            sum: AtomicU64::default(),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, le) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= le {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} histogram");
+        for (bucket, le) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            let count = bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{le=\"{le}\"}} {count}");
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = self.sum.load(Ordering::Relaxed) as f64 / 1e9;
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{name}_sum {sum}\n{name}_count {count}");
+    }
+}
+
+/// Everything the bot reports, rendered in the prometheus text format.
+#[derive(Default)]
+pub struct Metrics {
+    pub events: LabeledCounter,
+    pub words_submitted: AtomicU64,
+    pub failed_words: LabeledCounter,
+    pub word_lookup: Histogram,
+    pub commands: LabeledCounter,
+    connects: LabeledCounter,
+    reconnects: LabeledCounter,
+}
+
+impl Metrics {
+    pub fn word_submitted(&self) {
+        self.words_submitted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a connection of `socket`, every connection after the first is a reconnect.
+    pub fn connected(&self, socket: &str) {
+        if self.connects.get(socket) > 0 {
+            self.reconnects.inc(socket);
+        }
+        self.connects.inc(socket);
+    }
+
+    pub fn render(&self, mailbox_depth: usize) -> String {
+        let mut out = String::new();
+        self.events.render(
+            &mut out,
+            "kaoruko_events_received_total",
+            "event",
+            "Socket events received, by event name.",
+        );
+        let _ = writeln!(
+            out,
+            "# HELP kaoruko_words_submitted_total Words submitted by the bot.\n\
+             # TYPE kaoruko_words_submitted_total counter\n\
+             kaoruko_words_submitted_total {}",
+            self.words_submitted.load(Ordering::Relaxed)
+        );
+        self.failed_words.render(
+            &mut out,
+            "kaoruko_failed_words_total",
+            "reason",
+            "Words rejected by the game, by reason.",
+        );
+        self.word_lookup.render(
+            &mut out,
+            "kaoruko_word_lookup_seconds",
+            "Time taken to find a word for a syllable.",
+        );
+        let _ = writeln!(
+            out,
+            "# HELP kaoruko_mailbox_depth Messages waiting in the bot actor channel.\n\
+             # TYPE kaoruko_mailbox_depth gauge\n\
+             kaoruko_mailbox_depth {mailbox_depth}"
+        );
+        self.commands.render(
+            &mut out,
+            "kaoruko_commands_total",
+            "command",
+            "Chat commands run, by command name.",
+        );
+        self.reconnects.render(
+            &mut out,
+            "kaoruko_reconnects_total",
+            "socket",
+            "Reconnections to jklm, by socket.",
+        );
+        out
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves `GET /metrics` on `address` until the process exits.
+pub async fn serve(address: SocketAddr, bot: Arc<BotHandle>) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    tracing::info!("Serving metrics at http://{address}/metrics");
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let bot = Arc::clone(&bot);
+        tokio::spawn(async move {
+            let response = match read_request(&mut stream).await {
+                Ok(request) if request.method == "GET" && request.path == "/metrics" => {
+                    Response::new(
+                        200,
+                        "text/plain; version=0.0.4",
+                        METRICS.render(bot.mailbox_depth()),
+                    )
+                }
+                Ok(_) => Response::not_found(),
+                Err(err) => Response::text(400, err.to_string()),
+            };
+            if let Err(err) = write_response(&mut stream, response).await {
+                tracing::warn!("failed to answer metrics request: {err}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_reconnects() {
+        let metrics = Metrics::default();
+        metrics.connected("room");
+        metrics.connected("game");
+        metrics.connected("room");
+        let out = metrics.render(0);
+        assert!(out.contains("kaoruko_reconnects_total{socket=\"room\"} 1"));
+        assert!(!out.contains("kaoruko_reconnects_total{socket=\"game\"}"));
+    }
+}
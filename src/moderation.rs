@@ -0,0 +1,226 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::types::Auth;
+
+/// A chatter kept out of the rooms of the bot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    pub nickname: String,
+    // `service:id` of their account, guests have none
+    #[serde(default)]
+    pub identity: Option<String>,
+    pub banned_by: String,
+    // unix timestamp (in seconds)
+    pub banned_at: u64,
+}
+
+// how long `!mute` lasts without a duration
+const DEFAULT_MUTE_MINUTES: u64 = 10;
+// longest mute, a day
+const MAX_MUTE_MINUTES: u64 = 24 * 60;
+
+/// Who `!mute` is for and how long, such as `some player 30`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuteTarget {
+    pub nickname: String,
+    pub minutes: u64,
+}
+
+impl MuteTarget {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.minutes * 60)
+    }
+}
+
+impl std::str::FromStr for MuteTarget {
+    type Err = anyhow::Error;
+    // nicknames may have spaces, a trailing number is the duration
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (nickname, minutes) = match s.rsplit_once(char::is_whitespace) {
+            Some((nickname, minutes)) if minutes.chars().all(|c| c.is_ascii_digit()) => {
+                let minutes = minutes
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&minutes| minutes <= MAX_MUTE_MINUTES)
+                    .ok_or_else(|| {
+                        anyhow!("mute for at most {MAX_MUTE_MINUTES} minutes, found `{minutes}`")
+                    })?;
+                (nickname.trim_end(), minutes)
+            }
+            _ => (s, DEFAULT_MUTE_MINUTES),
+        };
+        Ok(Self {
+            nickname: nickname.to_string(),
+            minutes,
+        })
+    }
+}
+
+/// `service:id` of `auth`, none for guests.
+pub fn identity(auth: &Auth) -> Option<String> {
+    (!auth.id.is_empty()).then(|| format!("{}:{}", auth.service, auth.id))
+}
+
+/// Banned chatters, persisted as json so bans survive restarts.
+#[derive(Debug)]
+pub struct BanList {
+    path: PathBuf,
+    bans: Vec<Ban>,
+}
+
+impl BanList {
+    /// Loads the list from `path`, starting empty if the file does not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let bans = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<Vec<Ban>>(&content)
+                .map_err(|err| anyhow!("invalid bans in {}: {err}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, bans })
+    }
+
+    /// Bans `nickname`, and their account when they have one.
+    ///
+    /// Banning again updates the existing ban, matched by account or else by nickname.
+    pub fn ban(&mut self, nickname: &str, auth: Option<&Auth>, by: &str) -> anyhow::Result<()> {
+        let ban = Ban {
+            nickname: nickname.to_string(),
+            identity: auth.and_then(identity),
+            banned_by: by.to_string(),
+            banned_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        };
+        let existing = self.bans.iter_mut().find(|existing| match &ban.identity {
+            Some(_) => existing.identity == ban.identity,
+            None => existing.nickname.eq_ignore_ascii_case(nickname),
+        });
+        match existing {
+            Some(existing) => *existing = ban,
+            None => self.bans.push(ban),
+        }
+        self.save()
+    }
+
+    /// Lifts the bans of `chatter`, a nickname or a `service:id` account, returning whether
+    /// there was one.
+    pub fn unban(&mut self, chatter: &str) -> anyhow::Result<bool> {
+        let count = self.bans.len();
+        self.bans.retain(|ban| {
+            !ban.nickname.eq_ignore_ascii_case(chatter) && ban.identity.as_deref() != Some(chatter)
+        });
+        if self.bans.len() == count {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn bans(&self) -> &[Ban] {
+        &self.bans
+    }
+
+    /// Whether a chatter is banned, by their account or by their nickname.
+    pub fn is_banned(&self, nickname: &str, auth: Option<&Auth>) -> bool {
+        let identity = auth.and_then(identity);
+        self.bans.iter().any(|ban| {
+            ban.nickname.eq_ignore_ascii_case(nickname)
+                || (identity.is_some() && ban.identity == identity)
+        })
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        std::fs::write(&self.path, serde_json::to_string(&self.bans)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(id: &str) -> Auth {
+        Auth {
+            id: id.to_string(),
+            service: "discord".to_string(),
+            username: String::new(),
+        }
+    }
+
+    fn ban_list(name: &str) -> BanList {
+        let path = std::env::temp_dir().join(format!("kaoruko-{name}-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        BanList::load(path).unwrap()
+    }
+
+    #[test]
+    fn bans_by_account_and_nickname() {
+        let mut bans = ban_list("bans");
+        bans.ban("Spammer", Some(&auth("42")), "mod").unwrap();
+        assert!(bans.is_banned("spammer", None));
+        // renaming does not get around a ban of the account
+        assert!(bans.is_banned("someone else", Some(&auth("42"))));
+        assert!(!bans.is_banned("someone else", Some(&auth("7"))));
+        // guests share the empty id, which must not match
+        bans.ban("Guest", Some(&Auth::default()), "mod").unwrap();
+        assert!(!bans.is_banned("another guest", Some(&Auth::default())));
+    }
+
+    #[test]
+    fn bans_once() {
+        let mut bans = ban_list("once");
+        bans.ban("Spammer", Some(&auth("42")), "mod").unwrap();
+        bans.ban("Renamed", Some(&auth("42")), "mod").unwrap();
+        bans.ban("Guest", None, "mod").unwrap();
+        bans.ban("guest", None, "mod").unwrap();
+        assert_eq!(bans.bans.len(), 2);
+        assert_eq!(bans.bans[0].nickname, "Renamed");
+    }
+
+    #[test]
+    fn parses_mute_targets() {
+        let target = "some player 30".parse::<MuteTarget>().unwrap();
+        assert_eq!(
+            (target.nickname.as_str(), target.minutes),
+            ("some player", 30)
+        );
+        let target = "some player".parse::<MuteTarget>().unwrap();
+        assert_eq!(target.minutes, DEFAULT_MUTE_MINUTES);
+        assert_eq!("42".parse::<MuteTarget>().unwrap().nickname, "42");
+        assert!("spammer 99999999999999999999"
+            .parse::<MuteTarget>()
+            .is_err());
+        assert!("spammer 1441".parse::<MuteTarget>().is_err());
+    }
+
+    #[test]
+    fn persists_bans() {
+        let mut bans = ban_list("persisted");
+        bans.ban("Spammer", None, "mod").unwrap();
+        let mut reloaded = BanList::load(bans.path.clone()).unwrap();
+        assert!(reloaded.is_banned("spammer", None));
+        assert!(reloaded.unban("SPAMMER").unwrap());
+        assert!(!reloaded.unban("spammer").unwrap());
+        assert!(!BanList::load(bans.path.clone())
+            .unwrap()
+            .is_banned("spammer", None));
+        std::fs::write(&bans.path, "[{").unwrap();
+        assert!(BanList::load(bans.path).is_err());
+    }
+
+    #[test]
+    fn unbans_accounts() {
+        let mut bans = ban_list("accounts");
+        bans.ban("Spammer", Some(&auth("42")), "mod").unwrap();
+        assert!(!bans.unban("discord:7").unwrap());
+        assert!(bans.unban("discord:42").unwrap());
+        assert!(!bans.is_banned("renamed", Some(&auth("42"))));
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::anyhow;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::i18n::{fill, Locale, Message};
+
+/// Notable word a player can place, counted in their stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Perk {
+    Long,
+    Hyphen,
+    Sub,
+    Multi,
+}
+
+impl Perk {
+    fn message(self) -> Message {
+        match self {
+            Self::Long => Message::PerkLong,
+            Self::Hyphen => Message::PerkHyphen,
+            Self::Sub => Message::PerkSub,
+            Self::Multi => Message::PerkMulti,
+        }
+    }
+}
+
+/// How perks are counted and announced, read from the `[perks]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PerksConfig {
+    // letters a word needs to count as a long
+    pub long_length: usize,
+    // perks announced in the room, the others are only counted
+    pub announce: Vec<Perk>,
+    // replaces the announcement of the room language, with {nickname}, {perks} and {word}
+    pub message: Option<String>,
+    // replace how each perk is written in it, with {count}
+    pub templates: HashMap<Perk, String>,
+}
+
+impl Default for PerksConfig {
+    fn default() -> Self {
+        Self {
+            long_length: 20,
+            announce: vec![Perk::Long, Perk::Hyphen, Perk::Sub, Perk::Multi],
+            message: None,
+            templates: HashMap::new(),
+        }
+    }
+}
+
+impl PerksConfig {
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.long_length == 0 {
+            return Err(anyhow!("long_length must be at least 1"));
+        }
+        Ok(())
+    }
+
+    pub fn is_long(&self, word: &str) -> bool {
+        word.chars().count() >= self.long_length
+    }
+
+    /// Announcement of `perks`, each with the new count of the player, if any is announced.
+    pub fn announcement(
+        &self,
+        locale: Locale,
+        nickname: &str,
+        word: &str,
+        perks: &[(Perk, u64)],
+    ) -> Option<String> {
+        let perks = perks
+            .iter()
+            .filter(|(perk, _)| self.announce.contains(perk))
+            .map(|(perk, count)| match self.templates.get(perk) {
+                Some(template) => fill(template, &[("count", count)]),
+                None => locale.format(perk.message(), &[("count", count)]),
+            })
+            .collect::<Vec<_>>();
+        if perks.is_empty() {
+            return None;
+        }
+        let args: &[(&str, &dyn std::fmt::Display)] = &[
+            ("nickname", &nickname),
+            ("perks", &perks.join(" — ")),
+            ("word", &word),
+        ];
+        Some(match &self.message {
+            Some(template) => fill(template, args),
+            None => locale.format(Message::Perk, args),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announces_selected_perks() {
+        let config = PerksConfig {
+            announce: vec![Perk::Hyphen, Perk::Sub],
+            ..PerksConfig::default()
+        };
+        let perks = [(Perk::Long, 4), (Perk::Hyphen, 2)];
+        assert_eq!(
+            config
+                .announcement(Locale::En, "mika", "well-being", &perks)
+                .as_deref(),
+            Some("mika has placed a hyphen (2): well-being")
+        );
+        assert_eq!(
+            config.announcement(Locale::En, "mika", "x", &[(Perk::Long, 1)]),
+            None
+        );
+    }
+
+    #[test]
+    fn fills_configured_templates() {
+        let config = toml::from_str::<PerksConfig>(
+            r#"
+            long_length = 12
+            message = "{word} by {nickname}! {perks}"
+            templates.long = "long #{count}"
+            "#,
+        )
+        .unwrap();
+        assert!(config.is_long("abcdefghijkl"));
+        assert!(!config.is_long("abcdefghijk"));
+        let perks = [(Perk::Long, 3), (Perk::Sub, 1)];
+        assert_eq!(
+            config
+                .announcement(Locale::Fr, "mika", "abcdefghijkl", &perks)
+                .as_deref(),
+            Some("abcdefghijkl by mika! long #3 — un sn (1)")
+        );
+        assert!(toml::from_str::<PerksConfig>("announce = [\"short\"]").is_err());
+    }
+}
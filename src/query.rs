@@ -0,0 +1,371 @@
+use anyhow::anyhow;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::types::Dictionary;
+
+// limits of compiled patterns, `a{1000}{1000}` and the like are refused
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const DFA_SIZE_LIMIT: usize = 1 << 20;
+// words scanned between two looks at the clock
+const BUDGET_CHECK_INTERVAL: usize = 1024;
+// no query has more pages than this
+const MAX_PAGE: usize = 1000;
+
+/// A `!search` query, such as `ab len:8- no:e sort:rarest page 2`.
+///
+/// Words that are not filters form a regex, the current syllable being searched without one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub pattern: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    // letters the words must contain, and must not
+    pub letters: Vec<char>,
+    pub avoided: Vec<char>,
+    pub hyphen: Option<bool>,
+    pub space: Option<bool>,
+    // not played yet this round
+    pub unused: bool,
+    pub sort: Option<Sort>,
+    // from 1
+    pub page: Option<usize>,
+}
+
+impl Query {
+    /// Whether `word` passes every filter but the pattern and `unused`.
+    pub fn accepts(&self, word: &str) -> bool {
+        let length = word.chars().count();
+        self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+            && self.letters.iter().all(|&letter| word.contains(letter))
+            && !self.avoided.iter().any(|&letter| word.contains(letter))
+            && self
+                .hyphen
+                .is_none_or(|hyphen| word.contains('-') == hyphen)
+            && self.space.is_none_or(|space| word.contains(' ') == space)
+    }
+}
+
+/// Why a query could not be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchError {
+    // with the message of the regex parser
+    InvalidRegex(String),
+    TooExpensive,
+    TimedOut,
+}
+
+impl Query {
+    /// Words of `dictionary` matching the query, `pattern` standing in for a missing one.
+    ///
+    /// Ranked by `sort`, in dictionary order otherwise, and given up on after `budget`. Words in
+    /// `skipped` are left out, such as those removed from the dictionary or already played.
+    pub fn run<'a>(
+        &self,
+        pattern: &str,
+        dictionary: &'a Dictionary,
+        skipped: &HashSet<String>,
+        budget: Duration,
+    ) -> Result<Vec<&'a String>, SearchError> {
+        let started = Instant::now();
+        let re = compile(self.pattern.as_deref().unwrap_or(pattern))?;
+        let mut result = Vec::new();
+        for (index, word) in dictionary.dictionary.iter().enumerate() {
+            if index % BUDGET_CHECK_INTERVAL == 0 && started.elapsed() > budget {
+                return Err(SearchError::TimedOut);
+            }
+            if re.is_match(word) && self.accepts(word) && !skipped.contains(word) {
+                result.push(word);
+            }
+        }
+        if let Some(sort) = self.sort {
+            sort.apply(&mut result, &dictionary.syllables);
+        }
+        Ok(result)
+    }
+}
+
+// refuses patterns which would take too much memory to run
+fn compile(pattern: &str) -> Result<Regex, SearchError> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(DFA_SIZE_LIMIT)
+        .build()
+        .map_err(|err| match err {
+            regex::Error::CompiledTooBig(_) => SearchError::TooExpensive,
+            // the last line of a syntax error explains it
+            err => SearchError::InvalidRegex(
+                err.to_string()
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .trim_start_matches("error: ")
+                    .to_string(),
+            ),
+        })
+}
+
+impl std::str::FromStr for Query {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = Self::default();
+        let mut pattern = Vec::new();
+        let mut terms = s.split_whitespace();
+        while let Some(term) = terms.next() {
+            if term == "unused" {
+                query.unused = true;
+                continue;
+            }
+            // `page 3`, as well as `page:3`
+            if term == "page" {
+                query.page = Some(parse_page(terms.next().unwrap_or_default())?);
+                continue;
+            }
+            // regexes such as `(?i:ab)` are not filters
+            let Some((key, value)) = term.split_once(':') else {
+                pattern.push(term);
+                continue;
+            };
+            match key {
+                "len" => (query.min_length, query.max_length) = parse_length(value)?,
+                "has" => query.letters = value.chars().collect(),
+                "no" => query.avoided = value.chars().collect(),
+                "hyphen" => query.hyphen = Some(parse_switch(key, value)?),
+                "space" => query.space = Some(parse_switch(key, value)?),
+                "sort" => query.sort = Some(value.parse()?),
+                "page" => query.page = Some(parse_page(value)?),
+                _ => pattern.push(term),
+            }
+        }
+        if !pattern.is_empty() {
+            query.pattern = Some(pattern.join(" "));
+        }
+        Ok(query)
+    }
+}
+
+// `8`, `5-10`, `12-` or `-6`
+fn parse_length(value: &str) -> anyhow::Result<(Option<usize>, Option<usize>)> {
+    let invalid = || anyhow!("invalid length `{value}`, expected e.g. len:8, len:5-10 or len:12-");
+    let bound = |bound: &str| match bound {
+        "" => Ok(None),
+        bound => bound.parse::<usize>().map(Some).map_err(|_| invalid()),
+    };
+    let (min, max) = match value.split_once('-') {
+        Some((min, max)) => (bound(min)?, bound(max)?),
+        None => {
+            let length = bound(value)?.ok_or_else(invalid)?;
+            (Some(length), Some(length))
+        }
+    };
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(invalid());
+        }
+    }
+    Ok((min, max))
+}
+
+fn parse_page(value: &str) -> anyhow::Result<usize> {
+    match value.parse::<usize>() {
+        Ok(page) if (1..=MAX_PAGE).contains(&page) => Ok(page),
+        _ => Err(anyhow!(
+            "expected a page number from 1 to {MAX_PAGE} after `page`, found `{value}`"
+        )),
+    }
+}
+
+fn parse_switch(key: &str, value: &str) -> anyhow::Result<bool> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(anyhow!("expected yes or no for `{key}`, found `{value}`")),
+    }
+}
+
+/// Order of the results of a query, the order of the dictionary without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Longest,
+    Shortest,
+    // least common syllables first
+    Rarest,
+}
+
+impl Sort {
+    /// Sorts `words`, `syllables` being counted by `count_syllables`.
+    pub fn apply(self, words: &mut [&String], syllables: &HashMap<String, u32>) {
+        match self {
+            Self::Longest => words.sort_by_key(|word| std::cmp::Reverse(word.chars().count())),
+            Self::Shortest => words.sort_by_key(|word| word.chars().count()),
+            Self::Rarest => words.sort_by_cached_key(|word| rarity(word, syllables)),
+        }
+    }
+}
+
+impl std::str::FromStr for Sort {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "longest" => Ok(Self::Longest),
+            "shortest" => Ok(Self::Shortest),
+            "rarest" => Ok(Self::Rarest),
+            _ => Err(anyhow!(
+                "unknown sort `{s}`, expected one of: longest, shortest, rarest"
+            )),
+        }
+    }
+}
+
+// two letter syllables of `word`
+fn syllables(word: &str) -> HashSet<String> {
+    let letters = word.chars().collect::<Vec<_>>();
+    letters
+        .windows(2)
+        .filter(|pair| pair.iter().all(|letter| letter.is_alphabetic()))
+        .map(|pair| pair.iter().collect())
+        .collect()
+}
+
+/// Number of words containing each two letter syllable.
+pub fn count_syllables(words: &[String]) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for word in words {
+        for syllable in syllables(word) {
+            *counts.entry(syllable).or_default() += 1;
+        }
+    }
+    counts
+}
+
+// how many words share the least common syllable of `word`, lower is rarer
+fn rarity(word: &str, counts: &HashMap<String, u32>) -> u32 {
+    syllables(word)
+        .iter()
+        .map(|syllable| counts.get(syllable).copied().unwrap_or_default())
+        .min()
+        .unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_filters_around_the_pattern() {
+        let query = "^ab len:5-10 has:xy no:e hyphen:no unused sort:rarest"
+            .parse::<Query>()
+            .unwrap();
+        assert_eq!(
+            query,
+            Query {
+                pattern: Some("^ab".to_string()),
+                min_length: Some(5),
+                max_length: Some(10),
+                letters: vec!['x', 'y'],
+                avoided: vec!['e'],
+                hyphen: Some(false),
+                space: None,
+                unused: true,
+                sort: Some(Sort::Rarest),
+                page: None,
+            }
+        );
+        assert_eq!("ab page 3".parse::<Query>().unwrap().page, Some(3));
+        assert_eq!("page:2 ab".parse::<Query>().unwrap().page, Some(2));
+        assert!("ab page".parse::<Query>().is_err());
+        assert!("ab page 0".parse::<Query>().is_err());
+        assert!("ab page 18446744073709551615".parse::<Query>().is_err());
+        assert_eq!(
+            "(?i:ab) cd".parse::<Query>().unwrap().pattern.as_deref(),
+            Some("(?i:ab) cd")
+        );
+        assert_eq!("len:12-".parse::<Query>().unwrap().max_length, None);
+        assert!("len:10-5".parse::<Query>().is_err());
+        assert!("hyphen:maybe".parse::<Query>().is_err());
+        assert!("sort:oldest".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn filters_words() {
+        let query = "len:-8 has:a no:z space:yes".parse::<Query>().unwrap();
+        assert!(query.accepts("a cat"));
+        assert!(!query.accepts("a cat nap"));
+        assert!(!query.accepts("a zoo"));
+        assert!(!query.accepts("acat"));
+    }
+
+    #[test]
+    fn sorts_by_rarity() {
+        // `ab` is in both words, `ba` only in `aba`
+        let words = ["ab", "aba"].map(String::from);
+        let counts = count_syllables(&words);
+        let mut sorted = words.iter().collect::<Vec<_>>();
+        Sort::Rarest.apply(&mut sorted, &counts);
+        assert_eq!(sorted, ["aba", "ab"]);
+        Sort::Shortest.apply(&mut sorted, &counts);
+        assert_eq!(sorted, ["ab", "aba"]);
+    }
+
+    fn dictionary(words: &[&str]) -> Dictionary {
+        let dictionary = words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>();
+        Dictionary {
+            syllables: count_syllables(&dictionary),
+            dictionary,
+            sn: Vec::new(),
+            definitions: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn runs_queries() {
+        let dictionary = dictionary(&["abc", "abcdef", "xyz", "ab-ab"]);
+        let budget = Duration::from_secs(1);
+        // unsorted results keep the order of the dictionary
+        let query = "b".parse::<Query>().unwrap();
+        assert_eq!(
+            query.run("", &dictionary, &HashSet::new(), budget).unwrap(),
+            ["abc", "abcdef", "ab-ab"]
+        );
+        let query = "sort:longest".parse::<Query>().unwrap();
+        assert_eq!(
+            query
+                .run("ab", &dictionary, &HashSet::new(), budget)
+                .unwrap(),
+            ["abcdef", "ab-ab", "abc"]
+        );
+        let query = "b unused hyphen:no sort:shortest".parse::<Query>().unwrap();
+        let used = HashSet::from(["abc".to_string()]);
+        assert_eq!(
+            query.run("", &dictionary, &used, budget).unwrap(),
+            ["abcdef"]
+        );
+    }
+
+    #[test]
+    fn reports_why_queries_fail() {
+        let dictionary = dictionary(&["abc"]);
+        let budget = Duration::from_secs(1);
+        let run = |query: &str| {
+            query
+                .parse::<Query>()
+                .unwrap()
+                .run("", &dictionary, &HashSet::new(), budget)
+        };
+        assert_eq!(
+            run("(ab"),
+            Err(SearchError::InvalidRegex("unclosed group".to_string()))
+        );
+        assert_eq!(run("(a{1000}){1000}"), Err(SearchError::TooExpensive));
+        let query = Query::default();
+        assert_eq!(
+            query.run("a", &dictionary, &HashSet::new(), Duration::ZERO),
+            Err(SearchError::TimedOut)
+        );
+    }
+}
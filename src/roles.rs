@@ -0,0 +1,158 @@
+use serde::Deserialize;
+
+use crate::types::Auth;
+
+/// Role a chatter can hold, either from the room or from the role table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Developer,
+    Trusted,
+    Creator,
+    Leader,
+    Moderator,
+    Staff,
+}
+
+impl Role {
+    /// The role for a role name sent by jklm, if it is one we know of.
+    pub fn from_room(role: &str) -> Option<Self> {
+        match role {
+            "creator" => Some(Self::Creator),
+            "leader" => Some(Self::Leader),
+            "moderator" => Some(Self::Moderator),
+            "staff" => Some(Self::Staff),
+            _ => None,
+        }
+    }
+
+    // higher roles cannot be moderated by lower ones
+    fn rank(self) -> u8 {
+        match self {
+            Self::Leader => 1,
+            Self::Trusted => 2,
+            Self::Moderator => 3,
+            Self::Creator => 4,
+            Self::Developer => 5,
+            Self::Staff => 6,
+        }
+    }
+}
+
+/// Whether a chatter with `roles` may kick, ban or mute one with `target`, who must rank lower.
+pub fn outranks(roles: &[Role], target: &[Role]) -> bool {
+    let level = |roles: &[Role]| roles.iter().map(|role| role.rank()).max().unwrap_or(0);
+    level(roles) > level(target)
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Developer => "developer",
+            Self::Trusted => "trusted",
+            Self::Creator => "creator",
+            Self::Leader => "leader",
+            Self::Moderator => "moderator",
+            Self::Staff => "staff",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Auth ids granted roles regardless of the room, read from the `[roles]` section.
+///
+/// Nobody is listed by default, see `kaoruko.example.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RoleTable {
+    pub developers: Vec<String>,
+    // made moderator when they join the room
+    pub trusted: Vec<String>,
+    // owners of the room, jklm sees the bot as its creator since the bot opens it
+    pub creators: Vec<String>,
+}
+
+impl RoleTable {
+    /// Every role held by a chatter with `room_roles` and `auth`.
+    pub fn resolve(&self, room_roles: &[String], auth: Option<&Auth>) -> Vec<Role> {
+        let mut roles = room_roles
+            .iter()
+            .filter_map(|role| Role::from_room(role))
+            .collect::<Vec<_>>();
+        // guests have no id, which must not match anything in the table
+        let Some(id) = auth
+            .map(|auth| auth.id.as_str())
+            .filter(|id| !id.is_empty())
+        else {
+            return roles;
+        };
+        if self.developers.iter().any(|developer| developer == id) {
+            roles.push(Role::Developer);
+        }
+        if self.trusted.iter().any(|trusted| trusted == id) {
+            roles.push(Role::Trusted);
+        }
+        if self.creators.iter().any(|creator| creator == id) && !roles.contains(&Role::Creator) {
+            roles.push(Role::Creator);
+        }
+        roles
+    }
+
+    /// Whether the chatter with `auth` should be made moderator of the room.
+    pub fn is_trusted(&self, auth: &Auth) -> bool {
+        self.resolve(&[], Some(auth))
+            .iter()
+            .any(|role| matches!(role, Role::Developer | Role::Trusted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(id: &str) -> Auth {
+        Auth {
+            id: id.to_string(),
+            ..Auth::default()
+        }
+    }
+
+    #[test]
+    fn maps_room_roles() {
+        let table = RoleTable::default();
+        let roles = ["leader".to_string(), "bot".to_string(), "staff".to_string()];
+        assert_eq!(table.resolve(&roles, None), [Role::Leader, Role::Staff]);
+    }
+
+    #[test]
+    fn grants_roles_from_the_table() {
+        let table = RoleTable {
+            developers: vec!["1".to_string()],
+            trusted: vec!["2".to_string()],
+            // a blank entry must not match guests
+            creators: vec!["2".to_string(), String::new()],
+        };
+        assert_eq!(table.resolve(&[], Some(&auth("1"))), [Role::Developer]);
+        assert_eq!(
+            table.resolve(&[], Some(&auth("2"))),
+            [Role::Trusted, Role::Creator]
+        );
+        assert!(table.is_trusted(&auth("1")));
+        assert!(!table.is_trusted(&auth("3")));
+        assert!(table.resolve(&[], Some(&auth(""))).is_empty());
+        assert!(RoleTable::default()
+            .resolve(&[], Some(&auth("1")))
+            .is_empty());
+    }
+
+    #[test]
+    fn ranks_roles() {
+        assert!(outranks(&[Role::Moderator], &[]));
+        assert!(outranks(&[Role::Leader, Role::Creator], &[Role::Moderator]));
+        assert!(!outranks(&[Role::Moderator], &[Role::Moderator]));
+        assert!(!outranks(
+            &[Role::Moderator],
+            &[Role::Trusted, Role::Developer]
+        ));
+        assert!(!outranks(&[], &[]));
+    }
+}
@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+use serde::Serialize;
+
+// most lives jklm lets a player have
+const MAX_LIVES: u64 = 10;
+
+/// Bombparty rules to change, as sent with `setRules`, the others being left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rules {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starting_lives: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lives: Option<u64>,
+    // in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_bomb_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bomb_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_difficulty: Option<Difficulty>,
+    #[serde(rename = "dictionaryId", skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bonus_alphabet: Option<String>,
+}
+
+impl Rules {
+    /// Many lives and easy prompts, to learn new words.
+    pub fn practice() -> Self {
+        Self {
+            starting_lives: Some(5),
+            max_lives: Some(MAX_LIVES),
+            prompt_difficulty: Some(Difficulty::Beginner),
+            ..Self::default()
+        }
+    }
+
+    /// Few lives and hard prompts.
+    pub fn competitive() -> Self {
+        Self {
+            starting_lives: Some(2),
+            max_lives: Some(3),
+            prompt_difficulty: Some(Difficulty::Hard),
+            ..Self::default()
+        }
+    }
+}
+
+impl std::str::FromStr for Rules {
+    type Err = anyhow::Error;
+    // `lives 2-3`, `bomb 5-20`, `difficulty hard`, `dictionary fr`, `alphabet abc` or `preset practice`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rule, value) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        let value = value.trim();
+        let mut rules = Self::default();
+        match rule {
+            "lives" => {
+                let (starting, max) = parse_range(rule, value)?;
+                if starting == 0 || max.unwrap_or(starting) > MAX_LIVES {
+                    return Err(anyhow!("lives must be between 1 and {MAX_LIVES}"));
+                }
+                rules.starting_lives = Some(starting);
+                rules.max_lives = max;
+            }
+            "bomb" => {
+                let (min, max) = parse_range(rule, value)?;
+                rules.min_bomb_duration = Some(min);
+                rules.max_bomb_duration = Some(max.unwrap_or(min));
+            }
+            "difficulty" => rules.prompt_difficulty = Some(value.parse()?),
+            "dictionary" => {
+                // `en`, `fr` or `pt-BR`
+                let (language, region) = value.split_once('-').unwrap_or((value, "AA"));
+                if language.len() != 2
+                    || region.len() != 2
+                    || !language.chars().all(|c| c.is_ascii_lowercase())
+                    || !region.chars().all(|c| c.is_ascii_uppercase())
+                {
+                    return Err(anyhow!(
+                        "unknown dictionary `{value}`, expected a language such as en, fr or pt-BR"
+                    ));
+                }
+                rules.dictionary = Some(value.to_string());
+            }
+            "alphabet" => {
+                let letters = value.to_lowercase();
+                let mut seen = Vec::new();
+                for letter in letters.chars() {
+                    if !letter.is_ascii_lowercase() || seen.contains(&letter) {
+                        return Err(anyhow!(
+                            "the bonus alphabet must be distinct letters, found `{letter}`"
+                        ));
+                    }
+                    seen.push(letter);
+                }
+                if seen.is_empty() {
+                    return Err(anyhow!("the bonus alphabet needs at least one letter"));
+                }
+                rules.bonus_alphabet = Some(letters);
+            }
+            "preset" => {
+                rules = match value {
+                    "practice" => Self::practice(),
+                    "competitive" => Self::competitive(),
+                    _ => {
+                        return Err(anyhow!(
+                            "unknown preset `{value}`, expected one of: practice, competitive"
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(anyhow!(
+                    "unknown rule `{rule}`, expected one of: lives, bomb, difficulty, dictionary, alphabet, preset"
+                ))
+            }
+        }
+        Ok(rules)
+    }
+}
+
+// `3` or `2-4`
+fn parse_range(rule: &str, value: &str) -> anyhow::Result<(u64, Option<u64>)> {
+    let invalid =
+        || anyhow!("expected a number or a range such as 2-4 for `{rule}`, found `{value}`");
+    let (min, max) = match value.split_once('-') {
+        Some((min, max)) => (min, Some(max)),
+        None => (value, None),
+    };
+    let min = min.parse::<u64>().map_err(|_| invalid())?;
+    let max = max
+        .map(|max| max.parse::<u64>().map_err(|_| invalid()))
+        .transpose()?;
+    if max.is_some_and(|max| max < min) {
+        return Err(invalid());
+    }
+    Ok((min, max))
+}
+
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        match (self.starting_lives, self.max_lives) {
+            (Some(starting), Some(max)) => parts.push(format!("lives: {starting}-{max}")),
+            (Some(starting), None) => parts.push(format!("lives: {starting}")),
+            _ => {}
+        }
+        if let (Some(min), Some(max)) = (self.min_bomb_duration, self.max_bomb_duration) {
+            parts.push(format!("bomb: {min}-{max}s"));
+        }
+        if let Some(difficulty) = self.prompt_difficulty {
+            parts.push(format!("difficulty: {difficulty}"));
+        }
+        if let Some(dictionary) = &self.dictionary {
+            parts.push(format!("dictionary: {dictionary}"));
+        }
+        if let Some(alphabet) = &self.bonus_alphabet {
+            parts.push(format!("alphabet: {alphabet}"));
+        }
+        f.write_str(&parts.join(" — "))
+    }
+}
+
+/// How common the prompts are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Beginner,
+    Medium,
+    Hard,
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "beginner" => Ok(Self::Beginner),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(anyhow!(
+                "unknown difficulty `{s}`, expected one of: beginner, medium, hard"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Beginner => "beginner",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rule_changes() {
+        let rules = "lives 2-4".parse::<Rules>().unwrap();
+        assert_eq!((rules.starting_lives, rules.max_lives), (Some(2), Some(4)));
+        let rules = "bomb 8".parse::<Rules>().unwrap();
+        assert_eq!(
+            (rules.min_bomb_duration, rules.max_bomb_duration),
+            (Some(8), Some(8))
+        );
+        assert_eq!(
+            "dictionary pt-BR"
+                .parse::<Rules>()
+                .unwrap()
+                .dictionary
+                .as_deref(),
+            Some("pt-BR")
+        );
+        assert_eq!(
+            "preset practice".parse::<Rules>().unwrap(),
+            Rules::practice()
+        );
+        assert!("lives 0".parse::<Rules>().is_err());
+        assert!("lives 4-2".parse::<Rules>().is_err());
+        assert!("alphabet abca".parse::<Rules>().is_err());
+        assert!("dictionary english".parse::<Rules>().is_err());
+        assert!("gravity 2".parse::<Rules>().is_err());
+    }
+
+    #[test]
+    fn sends_only_changed_rules() {
+        let rules = "difficulty hard".parse::<Rules>().unwrap();
+        assert_eq!(
+            serde_json::to_value(&rules).unwrap(),
+            serde_json::json!({ "promptDifficulty": "hard" })
+        );
+        assert_eq!(
+            Rules::competitive().to_string(),
+            "lives: 2-3 — difficulty: hard"
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n::{Locale, Message};
+use crate::types::PlayerStats;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Stat a leaderboard can be ranked by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Words,
+    Subs,
+    Longs,
+    Hyphens,
+    Multi,
+    Lives,
+}
+
+impl std::str::FromStr for Category {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "words" => Ok(Self::Words),
+            "subs" => Ok(Self::Subs),
+            "longs" => Ok(Self::Longs),
+            "hyphens" => Ok(Self::Hyphens),
+            "multi" => Ok(Self::Multi),
+            "lives" => Ok(Self::Lives),
+            _ => Err(anyhow!(
+                "unknown stat `{s}`, expected one of: words, subs, longs, hyphens, multi, lives"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Words => "words",
+            Self::Subs => "subs",
+            Self::Longs => "longs",
+            Self::Hyphens => "hyphens",
+            Self::Multi => "multi",
+            Self::Lives => "lives",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Time range a leaderboard is computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Today,
+    Week,
+    AllTime,
+}
+
+impl Window {
+    // earliest timestamp (in seconds) included in the window
+    fn since(&self, now: u64) -> u64 {
+        match self {
+            Self::Today => now - now % DAY,
+            Self::Week => now.saturating_sub(7 * DAY),
+            Self::AllTime => 0,
+        }
+    }
+}
+
+impl std::str::FromStr for Window {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "today" => Ok(Self::Today),
+            "week" => Ok(Self::Week),
+            "all" | "alltime" | "all-time" => Ok(Self::AllTime),
+            _ => Err(anyhow!(
+                "unknown time window `{s}`, expected one of: today, week, all-time"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Today => "today",
+            Self::Week => "week",
+            Self::AllTime => "all-time",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub nickname: String,
+    pub words: u64,
+    pub subs: u64,
+    pub longs: u64,
+    pub hyphens: u64,
+    pub multi: u64,
+    pub lives: u64,
+    #[serde(default)]
+    pub longest_word: String,
+}
+
+impl PlayerRecord {
+    pub fn get(&self, category: Category) -> u64 {
+        match category {
+            Category::Words => self.words,
+            Category::Subs => self.subs,
+            Category::Longs => self.longs,
+            Category::Hyphens => self.hyphens,
+            Category::Multi => self.multi,
+            Category::Lives => self.lives,
+        }
+    }
+}
+
+impl From<&PlayerStats> for PlayerRecord {
+    fn from(stats: &PlayerStats) -> Self {
+        Self {
+            nickname: stats.nickname.clone(),
+            words: stats.words,
+            subs: stats.subs,
+            longs: stats.longs,
+            hyphens: stats.hyphens,
+            multi: stats.multi,
+            lives: stats.lives,
+            longest_word: stats.longest_word.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub room_code: String,
+    // unix timestamp (in seconds) of the end of the game
+    pub finished_at: u64,
+    pub players: Vec<PlayerRecord>,
+}
+
+impl GameRecord {
+    pub fn new<'a>(room_code: &str, players: impl Iterator<Item = &'a PlayerStats>) -> Self {
+        Self {
+            room_code: room_code.to_string(),
+            finished_at: now(),
+            players: players.map(PlayerRecord::from).collect(),
+        }
+    }
+}
+
+/// Finished games, persisted as json so stats survive restarts.
+///
+/// Every game is kept, one json line each, so the file grows with the games played.
+#[derive(Debug)]
+pub struct StatsStore {
+    path: PathBuf,
+    games: Vec<GameRecord>,
+}
+
+impl StatsStore {
+    /// Loads the store from `path`, starting empty if the file does not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let games = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<GameRecord>(line).map_err(|err| {
+                    anyhow!(
+                        "invalid game on line {} of {}: {err}",
+                        index + 1,
+                        path.display()
+                    )
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { path, games })
+    }
+
+    /// Appends `game` to the file, the games recorded before are left as they are.
+    pub fn record(&mut self, game: GameRecord) -> anyhow::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&game)?)?;
+        self.games.push(game);
+        Ok(())
+    }
+
+    /// Totals per nickname in descending order, leaving out players who scored nothing.
+    /// Only games of `room` are counted when it is given, `ongoing` is always counted.
+    pub fn leaders(
+        &self,
+        category: Category,
+        window: Window,
+        room: Option<&str>,
+        ongoing: Option<&GameRecord>,
+    ) -> Vec<(String, u64)> {
+        let since = window.since(now());
+        let mut totals = HashMap::<&str, u64>::new();
+        let games = self
+            .games
+            .iter()
+            .filter(|game| game.finished_at >= since)
+            .filter(|game| room.is_none_or(|room| game.room_code == room))
+            .chain(ongoing);
+        for game in games {
+            for player in &game.players {
+                *totals.entry(player.nickname.as_str()).or_default() += player.get(category);
+            }
+        }
+        let mut leaders = totals
+            .into_iter()
+            .filter(|(_, total)| *total > 0)
+            .map(|(nickname, total)| (nickname.to_string(), total))
+            .collect::<Vec<_>>();
+        leaders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        leaders
+    }
+}
+
+pub fn format_leaders(leaders: &[(String, u64)], limit: usize, locale: Locale) -> String {
+    if leaders.is_empty() {
+        return locale.text(Message::NobodyYet);
+    }
+    leaders
+        .iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (nickname, total))| format!("{}. {nickname} ({total})", i + 1))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn format_rank(leaders: &[(String, u64)], nickname: &str, locale: Locale) -> String {
+    match leaders.iter().position(|(n, _)| n == nickname) {
+        Some(index) => locale.format(
+            Message::RankOf,
+            &[
+                ("rank", &(index + 1)),
+                ("count", &leaders.len()),
+                ("total", &leaders[index].1),
+            ],
+        ),
+        None => locale.text(Message::Unranked),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(room_code: &str, days_ago: u64, players: &[(&str, u64)]) -> GameRecord {
+        GameRecord {
+            room_code: room_code.to_string(),
+            finished_at: now() - days_ago * DAY,
+            players: players
+                .iter()
+                .map(|&(nickname, words)| PlayerRecord {
+                    nickname: nickname.to_string(),
+                    words,
+                    subs: 0,
+                    longs: 0,
+                    hyphens: 0,
+                    multi: 0,
+                    lives: 0,
+                    longest_word: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn store(name: &str) -> StatsStore {
+        let path =
+            std::env::temp_dir().join(format!("kaoruko-{name}-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        StatsStore::load(path).unwrap()
+    }
+
+    fn leaders(total: &[(&str, u64)]) -> Vec<(String, u64)> {
+        total
+            .iter()
+            .map(|&(nickname, total)| (nickname.to_string(), total))
+            .collect()
+    }
+
+    #[test]
+    fn ranks_leaders_by_window_and_room() {
+        let mut stats = store("leaders");
+        stats
+            .record(game("ABCD", 0, &[("mika", 3), ("yuki", 0)]))
+            .unwrap();
+        stats.record(game("EFGH", 2, &[("yuki", 5)])).unwrap();
+        stats.record(game("ABCD", 30, &[("mika", 4)])).unwrap();
+        assert_eq!(
+            stats.leaders(Category::Words, Window::Today, None, None),
+            leaders(&[("mika", 3)])
+        );
+        assert_eq!(
+            stats.leaders(Category::Words, Window::Week, None, None),
+            leaders(&[("yuki", 5), ("mika", 3)])
+        );
+        assert_eq!(
+            stats.leaders(Category::Words, Window::AllTime, Some("ABCD"), None),
+            leaders(&[("mika", 7)])
+        );
+        // the game being played counts in every window and room
+        let ongoing = game("IJKL", 0, &[("yuki", 4)]);
+        assert_eq!(
+            stats.leaders(
+                Category::Words,
+                Window::AllTime,
+                Some("ABCD"),
+                Some(&ongoing)
+            ),
+            leaders(&[("mika", 7), ("yuki", 4)])
+        );
+        assert!(stats
+            .leaders(Category::Subs, Window::AllTime, None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn appends_games() {
+        let mut stats = store("appended");
+        stats.record(game("ABCD", 0, &[("mika", 3)])).unwrap();
+        stats.record(game("ABCD", 0, &[("mika", 2)])).unwrap();
+        let reloaded = StatsStore::load(stats.path.clone()).unwrap();
+        assert_eq!(reloaded.games.len(), 2);
+        std::fs::write(&stats.path, "{\"room_code\":").unwrap();
+        assert!(StatsStore::load(stats.path).is_err());
+    }
+
+    #[test]
+    fn formats_ranks() {
+        let leaders = leaders(&[("mika", 7), ("yuki", 4)]);
+        assert_eq!(format_rank(&leaders, "yuki", Locale::En), "#2 of 2 (4)");
+        assert_eq!(format_rank(&leaders, "sora", Locale::Fr), "non classé");
+        assert_eq!(format_leaders(&leaders, 1, Locale::En), "1. mika (7)");
+        assert_eq!(format_leaders(&[], 5, Locale::En), "nobody yet");
+    }
+}
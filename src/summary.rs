@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::i18n::{Locale, Message};
+use crate::stats::{Category, GameRecord, PlayerRecord};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Award {
+    pub nickname: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LongestWord {
+    pub nickname: String,
+    pub word: String,
+}
+
+/// Highlights of a finished game.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameSummary {
+    pub room_code: String,
+    pub finished_at: u64,
+    pub winner: Option<String>,
+    pub mvp: Option<Award>,
+    pub longest_word: Option<LongestWord>,
+    pub most_subs: Option<Award>,
+    pub most_hyphens: Option<Award>,
+    pub most_multi: Option<Award>,
+    pub bonus_lives: Vec<Award>,
+    pub players: Vec<PlayerRecord>,
+}
+
+impl GameSummary {
+    pub fn new(game: &GameRecord, winner: Option<String>) -> Self {
+        let longest_word = game
+            .players
+            .iter()
+            .filter(|p| !p.longest_word.is_empty())
+            .max_by_key(|p| p.longest_word.len())
+            .map(|p| LongestWord {
+                nickname: p.nickname.clone(),
+                word: p.longest_word.clone(),
+            });
+        let bonus_lives = game
+            .players
+            .iter()
+            .filter(|p| p.lives > 0)
+            .map(|p| Award {
+                nickname: p.nickname.clone(),
+                count: p.lives,
+            })
+            .collect();
+        Self {
+            room_code: game.room_code.clone(),
+            finished_at: game.finished_at,
+            winner,
+            mvp: most(&game.players, Category::Words),
+            longest_word,
+            most_subs: most(&game.players, Category::Subs),
+            most_hyphens: most(&game.players, Category::Hyphens),
+            most_multi: most(&game.players, Category::Multi),
+            bonus_lives,
+            players: game.players.clone(),
+        }
+    }
+
+    /// Compact one line version of the summary for the room chat.
+    pub fn to_chat(&self, locale: Locale) -> String {
+        let winner = match &self.winner {
+            Some(winner) => winner.clone(),
+            None => locale.text(Message::Nobody),
+        };
+        let mut parts = vec![locale.format(Message::Summary, &[("winner", &winner)])];
+        if let Some(Award { nickname, count }) = &self.mvp {
+            parts.push(locale.format(
+                Message::SummaryMvp,
+                &[("nickname", nickname), ("count", count)],
+            ));
+        }
+        if let Some(LongestWord { nickname, word }) = &self.longest_word {
+            parts.push(locale.format(
+                Message::SummaryLongest,
+                &[("nickname", nickname), ("word", word)],
+            ));
+        }
+        for (message, award) in [
+            (Message::SummarySubs, &self.most_subs),
+            (Message::SummaryHyphens, &self.most_hyphens),
+            (Message::SummaryMulti, &self.most_multi),
+        ] {
+            if let Some(Award { nickname, count }) = award {
+                parts.push(locale.format(message, &[("nickname", nickname), ("count", count)]));
+            }
+        }
+        if !self.bonus_lives.is_empty() {
+            let lives = self
+                .bonus_lives
+                .iter()
+                .map(|Award { nickname, count }| format!("{nickname} (+{count})"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            parts.push(locale.format(Message::SummaryLives, &[("lives", &lives)]));
+        }
+        parts.join(" — ")
+    }
+
+    /// Writes the summary as `<room code>-<timestamp>.json` inside `dir`.
+    pub fn write(&self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.json", self.room_code, self.finished_at));
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// player with the highest non zero count for the category
+fn most(players: &[PlayerRecord], category: Category) -> Option<Award> {
+    players
+        .iter()
+        .filter(|p| p.get(category) > 0)
+        .max_by_key(|p| p.get(category))
+        .map(|p| Award {
+            nickname: p.nickname.clone(),
+            count: p.get(category),
+        })
+}
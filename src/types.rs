@@ -1,11 +1,10 @@
 use anyhow::anyhow;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
-use std::cell::RefCell;
 use std::collections::HashMap;
 
 // the defalut implementation is used inside kaoruko_derive
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct Auth {
     pub id: String,
     pub service: String,
@@ -26,10 +25,13 @@ pub struct Chatter {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Dictionary {
-    pub dictionary: RefCell<Vec<String>>,
+    pub dictionary: Vec<String>,
     #[serde(skip)]
     pub syllables: HashMap<String, u32>,
     pub sn: Vec<String>,
+    // by word, read from their own file
+    #[serde(skip)]
+    pub definitions: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -67,6 +69,8 @@ pub struct NewChatter {
     pub auth: Auth,
     pub nickname: String,
     pub peer_id: u64,
+    #[serde(default)]
+    pub roles: Vec<String>,
 }
 
 fn handle_null<'de, D>(deserializer: D) -> Result<Auth, D::Error>
@@ -91,7 +95,7 @@ pub struct Setup {
     // pub players: Vec<idk>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Constants {
     pub max_bomb_duration: u64,
@@ -103,7 +107,7 @@ pub struct Constants {
     pub submit_rate_limit: SubmitRateLimit,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubmitRateLimit {
     pub interval: u64,
@@ -117,7 +121,7 @@ pub struct Milestone {
     pub rules_locked: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PlayerStats {
     pub nickname: String,
     pub roles: Vec<String>,
@@ -128,6 +132,7 @@ pub struct PlayerStats {
     pub multi: u64,
     pub lives: u64,
     pub streak: u64,
+    pub longest_word: String,
 }
 
 impl PlayerStats {
@@ -142,6 +147,7 @@ impl PlayerStats {
             multi: u64::default(),
             lives: u64::default(),
             streak: u64::default(),
+            longest_word: String::default(),
         }
     }
 }
@@ -185,3 +191,28 @@ impl TryFrom<Vec<Value>> for Player {
         Ok(serde_json::from_value::<Self>(value[0]["profile"].clone())?)
     }
 }
+
+/// How the bot picks a word among the ones matching the syllable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WordStrategy {
+    // first match in the shuffled dictionary
+    #[default]
+    Random,
+    Shortest,
+    Longest,
+}
+
+impl std::str::FromStr for WordStrategy {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "shortest" => Ok(Self::Shortest),
+            "longest" => Ok(Self::Longest),
+            _ => Err(anyhow!(
+                "unknown word strategy `{s}`, expected one of: random, shortest, longest"
+            )),
+        }
+    }
+}
@@ -3,7 +3,9 @@ use rand::Rng;
 use reqwest::{header::CONTENT_TYPE, Client};
 use rust_socketio::Payload;
 use serde_json::{json, Value};
-use std::cell::RefCell;
+
+// longest message jklm accepts in the chat
+pub const MAX_CHAT_LENGTH: usize = 300;
 
 pub fn create_user_token() -> anyhow::Result<String> {
     let mut rng = rand::rng();
@@ -63,15 +65,85 @@ pub fn text_payload(payload: Payload) -> Vec<Value> {
     }
 }
 
-pub fn shuffle(words: &mut RefCell<Vec<String>>) {
-    let mut words = words.borrow_mut();
+pub fn shuffle<T>(words: &mut [T]) {
     let mut current_index = words.len();
 
     while current_index != 0 {
         let random_index = (rand::rng().random_range(0.0..1.0) * current_index as f64) as usize;
         current_index -= 1;
 
-        [words[current_index], words[random_index]] =
-            [words[random_index].clone(), words[current_index].clone()];
+        words.swap(current_index, random_index);
+    }
+}
+
+/// `text` cut at the last word fitting in `max` characters, ellipsis included.
+pub fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut shortened = String::new();
+    for word in text.split_inclusive(' ') {
+        if shortened.chars().count() + word.trim_end().chars().count() + 1 > max {
+            break;
+        }
+        shortened.push_str(word);
+    }
+    // a single word longer than `max`
+    if shortened.is_empty() {
+        shortened = text.chars().take(max - 1).collect();
+    }
+    format!("{}…", shortened.trim_end())
+}
+
+/// `text` cut into messages of at most `max` characters, on word boundaries when possible.
+pub fn split(text: &str, max: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    for word in text.split_whitespace() {
+        // words longer than a whole message are cut anywhere
+        let mut word = word.to_string();
+        while word.chars().count() > max {
+            if !part.is_empty() {
+                parts.push(std::mem::take(&mut part));
+            }
+            let rest = word.chars().skip(max).collect();
+            parts.push(word.chars().take(max).collect());
+            word = rest;
+        }
+        if part.is_empty() {
+            part = word;
+        } else if part.chars().count() + 1 + word.chars().count() > max {
+            parts.push(std::mem::replace(&mut part, word));
+        } else {
+            part.push(' ');
+            part.push_str(&word);
+        }
+    }
+    if !part.is_empty() {
+        parts.push(part);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortens_on_word_boundaries() {
+        assert_eq!(shorten("a short one", 20), "a short one");
+        assert_eq!(shorten("a fairly long sentence", 14), "a fairly long…");
+        assert_eq!(shorten("unbreakable", 5), "unbr…");
+    }
+
+    #[test]
+    fn splits_on_word_boundaries() {
+        assert_eq!(split("a short one", 20), ["a short one"]);
+        assert_eq!(
+            split("a fairly long sentence", 14),
+            ["a fairly long", "sentence"]
+        );
+        assert_eq!(split("go unbreakable", 5), ["go", "unbre", "akabl", "e"]);
+        assert!(split("  ", 5).is_empty());
     }
 }